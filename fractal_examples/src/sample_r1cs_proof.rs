@@ -4,16 +4,14 @@
 // LICENSE file in the root directory of this source tree.
 
 use core::num;
-use std::cmp::max;
 
-use fractal_indexer::index::get_max_degree;
-use fractal_proofs::FriOptions;
 use fractal_prover::prover::FractalProver;
 use fractal_prover::FractalOptions;
+use fractal_utils::DEFAULT_PAR_THRESHOLD;
 use structopt::StructOpt;
 
 use fractal_indexer::{
-    index::{build_index_domains, Index, IndexParams},
+    index::{build_index_domains_with_blowup, Index, IndexParams},
     indexed_matrix::index_matrix,
     snark_keys::*,
 };
@@ -43,6 +41,7 @@ fn main() {
     orchestrate_r1cs_example::<BaseElement, BaseElement, Rp64_256, 1>(
         &options.arith_file,
         &options.wires_file,
+        options.max_remainder_size,
         options.verbose,
     );
 }
@@ -55,6 +54,7 @@ pub(crate) fn orchestrate_r1cs_example<
 >(
     arith_file: &str,
     wire_file: &str,
+    max_remainder_size: usize,
     verbose: bool,
 ) {
     let mut arith_parser = JsnarkArithReaderParser::<B>::new().unwrap();
@@ -71,27 +71,13 @@ pub(crate) fn orchestrate_r1cs_example<
     // let num_constraints = r1cs.clone().num_rows();
     // let num_non_zero = max(max(r1cs.A.l0_norm(), r1cs.B.l0_norm()), r1cs.C.l0_norm());
     // 1. Index this R1CS
-    let num_input_variables = r1cs.num_cols().next_power_of_two();
-    let num_non_zero = r1cs.max_num_nonzero().next_power_of_two();
-    let num_constraints = max(max(r1cs.A.l0_norm(), r1cs.B.l0_norm()), r1cs.C.l0_norm()).next_power_of_two();
-    let max_degree = get_max_degree(num_input_variables, num_non_zero, num_constraints);
-    // TODO: make the calculation of eta automated
-    let eta = B::GENERATOR.exp(B::PositiveInteger::from(2 * B::TWO_ADICITY));
-    let eta_k = B::GENERATOR.exp(B::PositiveInteger::from(1337 * B::TWO_ADICITY));
-    // if num_non_zero <= num_vars {
-    //     num_non_zero = num_non_zero * 2;
-    // }
+    let index_params = IndexParams::<B>::from_r1cs(&r1cs);
+    let eta = index_params.eta;
+    let eta_k = index_params.eta_k;
     println!("Eta is = {}", eta);
-    let index_params = IndexParams::<B> {
-        num_input_variables,
-        num_constraints,
-        num_non_zero,
-        max_degree,
-        eta,
-        eta_k,
-    };
 
-    let index_domains = build_index_domains::<B>(index_params.clone());
+    let lde_blowup = 4;
+    let index_domains = build_index_domains_with_blowup::<B>(index_params.clone(), lde_blowup);
     println!("build index domains");
     let indexed_a = index_matrix::<B>(&r1cs.A, &index_domains);
     let indexed_b = index_matrix::<B>(&r1cs.B, &index_domains);
@@ -114,9 +100,14 @@ pub(crate) fn orchestrate_r1cs_example<
     let summing_domain = index_domains.k_field;
     
     let h_domain = index_domains.h_field;
-    let lde_blowup = 4;
     let num_queries = 16;
-    let fri_options = FriOptions::new(lde_blowup, 4, 32);
+    let fri_options = FractalOptions::<B>::get_fri_options(
+        lde_blowup,
+        4,
+        max_remainder_size,
+        evaluation_domain.len(),
+    )
+    .unwrap();
     //println!("h_domain: {:?}, summing_domain: {:?}, evaluation_domain: {:?}", &h_domain, &summing_domain, &evaluation_domain);
     let options: FractalOptions<B> = FractalOptions::<B> {
         degree_fs,
@@ -129,8 +120,10 @@ pub(crate) fn orchestrate_r1cs_example<
         eta_k,
         fri_options,
         num_queries,
+        par_threshold: DEFAULT_PAR_THRESHOLD,
     };
     
+    let proof_options = options.proof_options();
     let pub_inputs_bytes = vec![0u8];
     let mut prover =
         FractalProver::<B, E, H>::new(prover_key, options, vec![], wires, pub_inputs_bytes.clone());
@@ -138,7 +131,7 @@ pub(crate) fn orchestrate_r1cs_example<
 
     println!(
         "Verified: {:?}",
-        fractal_verifier::verifier::verify_fractal_proof::<B, E, H>(verifier_key, proof.unwrap(), pub_inputs_bytes)
+        fractal_verifier::verifier::verify_fractal_proof::<B, E, H>(verifier_key, &proof_options, proof.unwrap(), pub_inputs_bytes)
     );
 }
 
@@ -161,6 +154,10 @@ struct ExampleOptions {
     )]
     wires_file: String,
 
+    /// Maximum number of elements in the FRI remainder.
+    #[structopt(short = "r", long = "max_remainder_size", default_value = "32")]
+    max_remainder_size: usize,
+
     /// Verbose logging and reporting.
     #[structopt(short = "v", long = "verbose")]
     verbose: bool,
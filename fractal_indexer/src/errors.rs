@@ -12,6 +12,8 @@ pub enum IndexerError {
     R1CS(R1CSError),
     /// If the Merkle Tree leads to an error
     MerkleTreeErr(MerkleTreeError),
+    /// Invalid index parameters: {0}
+    InvalidIndexParams(String),
 }
 
 impl From<R1CSError> for IndexerError {
@@ -3,22 +3,145 @@ use std::cmp::max;
 // TODO: This class will include the indexes of 3 matrices
 // Should domain info be in here or in a separate class?
 use winter_math::{fft, FieldElement, StarkField}; // utils
+use winter_utils::{ByteWriter, Serializable};
 
 type SmallFieldElement17 = fractal_math::smallprimefield::BaseElement<17, 3, 4>;
 
+use crate::errors::IndexerError;
 use crate::indexed_matrix::IndexedMatrix;
-use models::r1cs::R1CS;
+use models::r1cs::{Matrix, R1CS};
 
 #[derive(Clone, Debug)]
 pub struct IndexParams<E: StarkField> {
     pub num_input_variables: usize,
+    // The first num_public_inputs entries of the variable assignment are public. The prover's
+    // pub_inputs_bytes must be exactly these values, see public_inputs_to_bytes.
+    pub num_public_inputs: usize,
     // num_witness_variables: usize,
     pub num_constraints: usize,
+    // Size of the summing domain K. Each matrix is indexed over K on its own, so this has to be
+    // at least the max (not the sum) of the non-zero counts of A, B and C.
+    // See required_summing_domain_size.
     pub num_non_zero: usize,
     pub max_degree: usize,
     pub eta: E,
     pub eta_k: E,
 }
+
+impl<E: StarkField> IndexParams<E> {
+    /// Index parameters for an instance with the given sizes. All sizes are rounded up to
+    /// powers of two (num_non_zero to at least 2). eta and eta_k are set to the same fixed
+    /// coset offsets used by the examples. No inputs are marked public; set
+    /// `num_public_inputs` afterwards if the assignment starts with public inputs.
+    ///
+    /// # Panics
+    /// Panics if [IndexParams::try_new] would return an error.
+    pub fn new(num_input_variables: usize, num_constraints: usize, num_non_zero: usize) -> Self {
+        Self::try_new(num_input_variables, num_constraints, num_non_zero)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as [IndexParams::new], but returns an error instead of panicking if the rounded
+    /// sizes can't be indexed: H and K have to be nontrivial subgroups of the field, and the
+    /// coset offsets must not lie in them.
+    pub fn try_new(
+        num_input_variables: usize,
+        num_constraints: usize,
+        num_non_zero: usize,
+    ) -> Result<Self, IndexerError> {
+        let round = |name: &str, size: usize| match size.checked_next_power_of_two() {
+            Some(size) if size > 1 && size.trailing_zeros() <= E::TWO_ADICITY => Ok(size),
+            _ => Err(IndexerError::InvalidIndexParams(format!(
+                "{} {} must round to a power of two between 2 and 2^{}",
+                name,
+                size,
+                E::TWO_ADICITY
+            ))),
+        };
+        let num_input_variables = round("num_input_variables", num_input_variables)?;
+        let num_constraints = round("num_constraints", num_constraints)?;
+        let num_non_zero = round("num_non_zero", max(num_non_zero, 2))?;
+        let max_degree = get_max_degree(num_input_variables, num_constraints, num_non_zero);
+        let eta = E::GENERATOR.exp(E::PositiveInteger::from(2 * E::TWO_ADICITY));
+        let eta_k = E::GENERATOR.exp(E::PositiveInteger::from(1337 * E::TWO_ADICITY));
+        let h_field_size = max(num_input_variables, num_constraints);
+        if !is_valid_coset_offset(eta, h_field_size) {
+            return Err(IndexerError::InvalidIndexParams(format!(
+                "eta must not lie in the subgroup H of size {}",
+                h_field_size
+            )));
+        }
+        if !is_valid_coset_offset(eta_k, num_non_zero) {
+            return Err(IndexerError::InvalidIndexParams(format!(
+                "eta_k must not lie in the subgroup K of size {}",
+                num_non_zero
+            )));
+        }
+        Ok(IndexParams {
+            num_input_variables,
+            num_public_inputs: 0,
+            num_constraints,
+            num_non_zero,
+            max_degree,
+            eta,
+            eta_k,
+        })
+    }
+
+    /// Size of the evaluation domain L for the given FRI blowup factor, see [eval_domain_size].
+    /// Returns an error if the blowup isn't a power of two or L doesn't fit in the field.
+    pub fn checked_eval_domain_size(&self, lde_blowup: usize) -> Result<usize, IndexerError> {
+        match self.max_degree.checked_mul(lde_blowup) {
+            Some(size) if lde_blowup.is_power_of_two() && size.trailing_zeros() <= E::TWO_ADICITY => {
+                Ok(size)
+            }
+            _ => Err(IndexerError::InvalidIndexParams(format!(
+                "an evaluation domain of {} * {} elements is not a power of two of at most 2^{}",
+                self.max_degree,
+                lde_blowup,
+                E::TWO_ADICITY
+            ))),
+        }
+    }
+
+    /// The max_degree these parameters need, see [get_max_degree].
+    pub fn compute_max_degree(&self) -> usize {
+        get_max_degree(self.num_input_variables, self.num_constraints, self.num_non_zero)
+    }
+
+    /// Derives the index parameters for an R1CS instance, e.g. one produced by the jsnark
+    /// parsers in `models`. See [IndexParams::new].
+    ///
+    /// # Panics
+    /// Panics if [IndexParams::try_from_r1cs] would return an error.
+    pub fn from_r1cs(r1cs: &R1CS<E>) -> Self {
+        Self::try_from_r1cs(r1cs).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as [IndexParams::from_r1cs], but returns an error instead of panicking, see
+    /// [IndexParams::try_new].
+    pub fn try_from_r1cs(r1cs: &R1CS<E>) -> Result<Self, IndexerError> {
+        Self::try_new(
+            r1cs.num_cols(),
+            r1cs.num_rows(),
+            required_summing_domain_size(&r1cs.A, &r1cs.B, &r1cs.C),
+        )
+    }
+}
+
+impl<E: StarkField> Serializable for IndexParams<E> {
+    /// Serializes `self` and writes the resulting bytes into the `target` writer.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u64(self.num_input_variables as u64);
+        target.write_u64(self.num_public_inputs as u64);
+        target.write_u64(self.num_constraints as u64);
+        target.write_u64(self.num_non_zero as u64);
+        target.write_u64(self.max_degree as u64);
+        self.eta.write_into(target);
+        self.eta_k.write_into(target);
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Index<E: StarkField> {
     pub params: IndexParams<E>,
@@ -28,18 +151,42 @@ pub struct Index<E: StarkField> {
 }
 
 impl<E: StarkField> Index<E> {
+    /// # Panics
+    /// Panics if [Index::try_new] would return an error.
     pub fn new(
         params: IndexParams<E>,
         indexed_a: IndexedMatrix<E>,
         indexed_b: IndexedMatrix<E>,
         indexed_c: IndexedMatrix<E>,
     ) -> Self {
-        Index {
+        Self::try_new(params, indexed_a, indexed_b, indexed_c).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as [Index::new], but returns an error instead of panicking if `params.num_non_zero`
+    /// is too small to enumerate the non-zero entries of every matrix.
+    pub fn try_new(
+        params: IndexParams<E>,
+        indexed_a: IndexedMatrix<E>,
+        indexed_b: IndexedMatrix<E>,
+        indexed_c: IndexedMatrix<E>,
+    ) -> Result<Self, IndexerError> {
+        let required_num_non_zero = required_summing_domain_size(
+            &indexed_a.matrix,
+            &indexed_b.matrix,
+            &indexed_c.matrix,
+        );
+        if params.num_non_zero < required_num_non_zero {
+            return Err(IndexerError::InvalidIndexParams(format!(
+                "num_non_zero {} is too small, the matrices need a summing domain of size {}",
+                params.num_non_zero, required_num_non_zero
+            )));
+        }
+        Ok(Index {
             params,
             indexed_a: indexed_a,
             indexed_b: indexed_b,
             indexed_c: indexed_c,
-        }
+        })
     }
 }
 
@@ -73,6 +220,35 @@ pub struct IndexDomains<E: FieldElement> {
 /// Perhaps we can add a function "get_subgroup_of_size" or "get_generator_of_order"
 /// Generators are needed here since we'll need those for FFT-friendly subgroups anyway.
 pub fn build_index_domains<E: StarkField>(params: IndexParams<E>) -> IndexDomains<E> {
+    build_index_domains_with_blowup(params, 4)
+}
+
+/// Checks that `eta` is outside the multiplicative subgroup of size `subgroup_size`, i.e.
+/// `eta^subgroup_size != 1`. Otherwise the coset `eta * H` is just H again and the offset FFTs
+/// don't evaluate on a proper coset.
+pub fn is_valid_coset_offset<E: StarkField>(eta: E, subgroup_size: usize) -> bool {
+    eta.exp(E::PositiveInteger::from(subgroup_size as u64)) != E::ONE
+}
+
+/// Same as build_index_domains, but with an evaluation domain L sized for the given FRI
+/// blowup factor. The FRI options used to prove and verify must use the same blowup.
+///
+/// # Panics
+/// Panics if [try_build_index_domains_with_blowup] would return an error.
+pub fn build_index_domains_with_blowup<E: StarkField>(
+    params: IndexParams<E>,
+    lde_blowup: usize,
+) -> IndexDomains<E> {
+    try_build_index_domains_with_blowup(params, lde_blowup).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Same as [build_index_domains_with_blowup], but returns an error instead of panicking if the
+/// sizes in `params` aren't nontrivial powers of two, `max_degree` is too small for them, or
+/// `lde_blowup` doesn't give an evaluation domain in the field.
+pub fn try_build_index_domains_with_blowup<E: StarkField>(
+    params: IndexParams<E>,
+    lde_blowup: usize,
+) -> Result<IndexDomains<E>, IndexerError> {
     let num_input_variables = params.num_input_variables;
     let num_constraints = params.num_constraints;
     let num_non_zero = params.num_non_zero;
@@ -80,27 +256,35 @@ pub fn build_index_domains<E: StarkField>(params: IndexParams<E>) -> IndexDomain
 
     // Validate inputs.
     let ntpow2 = { |x: usize| x > 1 && (x & (x - 1) == 0) };
-    assert!(
-        ntpow2(num_input_variables),
-        "num_input_variables {} must be nontriv power of two",
-        num_input_variables
-    );
-    assert!(
-        ntpow2(num_constraints),
-        "num_constraints {} must be nontriv power of two",
-        num_constraints
-    );
-    assert!(
-        ntpow2(num_non_zero),
-        "num_non_zero {} must be nontriv power of two",
-        num_non_zero
-    );
-
-    assert!(
-        ntpow2(max_degree),
-        "max_degree {} must be nontriv power of two",
-        max_degree
-    );
+    for (name, size) in [
+        ("num_input_variables", num_input_variables),
+        ("num_constraints", num_constraints),
+        ("num_non_zero", num_non_zero),
+        ("max_degree", max_degree),
+    ] {
+        if !ntpow2(size) {
+            return Err(IndexerError::InvalidIndexParams(format!(
+                "{} {} must be nontriv power of two",
+                name, size
+            )));
+        }
+    }
+    if max_degree < params.compute_max_degree() {
+        return Err(IndexerError::InvalidIndexParams(format!(
+            "max_degree {} is too small for this instance, it must be at least {}",
+            max_degree,
+            params.compute_max_degree()
+        )));
+    }
+    if !lde_blowup.is_power_of_two() {
+        return Err(IndexerError::InvalidIndexParams(format!(
+            "lde_blowup {} must be a power of two",
+            lde_blowup
+        )));
+    }
+    // / |L| >= 3*k_field_size - 3. For the rest of our code, we need to use powers of 2, hence
+    // the blowup factor is at least 4.
+    let l_field_size = params.checked_eval_domain_size(lde_blowup)?;
 
     // Need to encode a subset of H field: indices of inputs.
     let i_field_size = num_input_variables;
@@ -117,8 +301,6 @@ pub fn build_index_domains<E: StarkField>(params: IndexParams<E>) -> IndexDomain
     let k_field_base = E::get_root_of_unity(k_field_size.trailing_zeros());
     let h_field_base = E::get_root_of_unity(h_field_size.trailing_zeros());
 
-    // / |L| >= 3*k_field_size - 3. For the rest of our code, we need to use powers of 2, hence we multiply by 4.
-    let l_field_size = 4 * max_degree; 
     let l_field_base = E::get_root_of_unity(l_field_size.trailing_zeros());
 
     
@@ -136,7 +318,7 @@ pub fn build_index_domains<E: StarkField>(params: IndexParams<E>) -> IndexDomain
     let inv_twiddles_k_elts = fft::get_inv_twiddles::<E>(k_field_size);
     let twiddles_l_elts = fft::get_twiddles::<E>(l_field_size);
 
-    IndexDomains {
+    Ok(IndexDomains {
         i_field_base: i_field_base,
         k_field_base: k_field_base,
         h_field_base: h_field_base,
@@ -149,7 +331,7 @@ pub fn build_index_domains<E: StarkField>(params: IndexParams<E>) -> IndexDomain
         twiddles_l_elts: twiddles_l_elts,
         eta: params.eta,
         eta_k: params.eta_k,
-    }
+    })
 }
 
 // Same as build_basefield_index_domains but for a prime field of size 17
@@ -252,7 +434,34 @@ pub fn create_primefield_index_from_r1cs(
     Index::new(params, indexed_a, indexed_b, indexed_c)
 }
 
-pub fn get_max_degree(num_input_variables: usize, _num_constraints: usize, num_non_zero: usize) -> usize {
-    let max_whole = max(num_input_variables - 1, max(2*num_non_zero-3, num_non_zero - 2)) + 1;
+/// Size of the evaluation domain L for polynomials of degree below `max_degree` at the given
+/// FRI blowup factor. The prover, the verifier and the index must all agree on this.
+pub fn eval_domain_size(max_degree: usize, blowup_factor: usize) -> usize {
+    max_degree * blowup_factor
+}
+
+/// Smallest power of two above the degree of every polynomial the prover runs FRI on: the
+/// e polynomials of the product sumcheck over H (degree |H| - 1) and of the matrix sumcheck
+/// over K (degree 2|K| - 3). H enumerates both rows and columns, so it is sized by the larger
+/// of num_input_variables and num_constraints. Degrees of tiny instances are clamped at 0.
+pub fn get_max_degree(num_input_variables: usize, num_constraints: usize, num_non_zero: usize) -> usize {
+    let h_field_size = max(num_input_variables, num_constraints);
+    let max_whole = max(
+        h_field_size.saturating_sub(1),
+        max((2 * num_non_zero).saturating_sub(3), num_non_zero.saturating_sub(2)),
+    ) + 1;
     max_whole.next_power_of_two()
-}   
\ No newline at end of file
+}   
+
+/// Returns the smallest valid size of the summing domain K for the matrices A, B and C.
+/// The linchecks for A, B and C each sum over K separately, so K only has to enumerate the
+/// non-zero entries of the densest matrix. Rounded up to a nontrivial power of two, as
+/// required by build_index_domains.
+pub fn required_summing_domain_size<E: StarkField>(
+    a: &Matrix<E>,
+    b: &Matrix<E>,
+    c: &Matrix<E>,
+) -> usize {
+    let max_non_zero = max(max(a.l0_norm(), b.l0_norm()), c.l0_norm());
+    max(max_non_zero, 2).next_power_of_two()
+}
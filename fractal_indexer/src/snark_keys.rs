@@ -8,15 +8,30 @@ use winter_fri::utils::hash_values;
 use models::r1cs::{Matrix, R1CS};
 use winter_crypto::{ElementHasher, MerkleTree};
 use winter_math::{FieldElement, StarkField, polynom};
-use winter_utils::transpose_slice;
+use winter_utils::{transpose_slice, ByteWriter, Serializable};
 
-#[derive(Debug)]  // Clone
+#[derive(Debug)]
 pub struct ProverIndexPolynomial<H: ElementHasher + ElementHasher<BaseField = E>, E: FieldElement> {
     pub polynomial: Vec<E>,
     pub evaluations: Vec<E>,
     pub tree: MerkleTree<H>,
 }
 
+// MerkleTree is not Clone, so the tree is rebuilt from its leaves. Clones of a prover key
+// therefore share nothing and can be used by independent provers, e.g. on separate threads.
+impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> Clone
+    for ProverIndexPolynomial<H, B>
+{
+    fn clone(&self) -> Self {
+        ProverIndexPolynomial {
+            polynomial: self.polynomial.clone(),
+            evaluations: self.evaluations.clone(),
+            tree: MerkleTree::new(self.tree.leaves().to_vec())
+                .expect("leaves of an existing tree are a valid tree"),
+        }
+    }
+}
+
 impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> ProverIndexPolynomial<H, B> {
     // TODO Add error checking, currently assumes index is
     // within range.
@@ -30,7 +45,7 @@ impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> ProverIndex
     }
 }
 
-#[derive(Debug)]  // Clone
+#[derive(Debug)]
 pub struct ProverMatrixIndex<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> {
     pub matrix: Matrix<B>,
     pub row_poly: ProverIndexPolynomial<H, B>,
@@ -38,6 +53,20 @@ pub struct ProverMatrixIndex<H: ElementHasher + ElementHasher<BaseField = B>, B:
     pub val_poly: ProverIndexPolynomial<H, B>,
 }
 
+// Written out since deriving Clone would require H: Clone.
+impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> Clone
+    for ProverMatrixIndex<H, B>
+{
+    fn clone(&self) -> Self {
+        ProverMatrixIndex {
+            matrix: self.matrix.clone(),
+            row_poly: self.row_poly.clone(),
+            col_poly: self.col_poly.clone(),
+            val_poly: self.val_poly.clone(),
+        }
+    }
+}
+
 impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> ProverMatrixIndex<H, B> {
     pub fn get_val_eval(&self, point: B) -> B {
         self.val_poly.get_eval_at_point(point)
@@ -59,9 +88,19 @@ impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> ProverMatri
     pub fn get_row_eval_at_index(&self, index: usize) -> B {
         self.row_poly.get_eval_at_index(index)
     }
+
+    /// The verifier's view of this matrix index, i.e. the roots of the row, col and val trees.
+    pub fn get_verifier_matrix_index(&self) -> VerifierMatrixIndex<H, B> {
+        VerifierMatrixIndex {
+            row_poly_commitment: *self.row_poly.tree.root(),
+            col_poly_commitment: *self.col_poly.tree.root(),
+            val_poly_commitment: *self.val_poly.tree.root(),
+        }
+    }
 }
 
-#[derive(Debug)]  // Clone
+/// Cloning a ProverKey makes a full, independent copy of the index and its Merkle trees.
+#[derive(Debug)]
 pub struct ProverKey<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> {
     pub params: IndexParams<B>,
     pub matrix_a_index: ProverMatrixIndex<H, B>,
@@ -69,6 +108,35 @@ pub struct ProverKey<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkFi
     pub matrix_c_index: ProverMatrixIndex<H, B>,
 }
 
+impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> Clone for ProverKey<H, B> {
+    fn clone(&self) -> Self {
+        ProverKey {
+            params: self.params.clone(),
+            matrix_a_index: self.matrix_a_index.clone(),
+            matrix_b_index: self.matrix_b_index.clone(),
+            matrix_c_index: self.matrix_c_index.clone(),
+        }
+    }
+}
+
+impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> ProverKey<H, B> {
+    /// Derives the matching verifier key from the committed index.
+    pub fn get_verifier_key(&self) -> VerifierKey<H, B> {
+        VerifierKey {
+            params: self.params.clone(),
+            matrix_a_commitments: self.matrix_a_index.get_verifier_matrix_index(),
+            matrix_b_commitments: self.matrix_b_index.get_verifier_matrix_index(),
+            matrix_c_commitments: self.matrix_c_index.get_verifier_matrix_index(),
+        }
+    }
+
+    /// A commitment to the preprocessed index, equal to [VerifierKey::commitment] of the
+    /// matching verifier key.
+    pub fn preprocessing_commitment(&self) -> H::Digest {
+        self.get_verifier_key().commitment()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct VerifierMatrixIndex<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> {
     pub row_poly_commitment: H::Digest,
@@ -76,6 +144,17 @@ pub struct VerifierMatrixIndex<H: ElementHasher + ElementHasher<BaseField = B>,
     pub val_poly_commitment: H::Digest,
 }
 
+impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> Serializable
+    for VerifierMatrixIndex<H, B>
+{
+    /// Serializes `self` and writes the resulting bytes into the `target` writer.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.row_poly_commitment.write_into(target);
+        self.col_poly_commitment.write_into(target);
+        self.val_poly_commitment.write_into(target);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct VerifierKey<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> {
     pub params: IndexParams<B>,
@@ -84,6 +163,31 @@ pub struct VerifierKey<H: ElementHasher + ElementHasher<BaseField = B>, B: Stark
     pub matrix_c_commitments: VerifierMatrixIndex<H, B>,
 }
 
+impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> VerifierKey<H, B> {
+    /// A single digest binding the index params and all nine matrix commitments.
+    /// A light verifier can hold just this value and check any full key it is
+    /// handed (the opening) against it before using that key.
+    pub fn commitment(&self) -> H::Digest {
+        let mut bytes = self.to_bytes();
+        // Rp64_256::hash in winter-crypto 0.4 panics on long inputs whose length isn't a
+        // multiple of 7. The key has a fixed layout, so zero padding is unambiguous.
+        bytes.resize((bytes.len() + 6) / 7 * 7, 0);
+        H::hash(&bytes)
+    }
+}
+
+impl<H: ElementHasher + ElementHasher<BaseField = B>, B: StarkField> Serializable
+    for VerifierKey<H, B>
+{
+    /// Serializes `self` and writes the resulting bytes into the `target` writer.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.params.write_into(target);
+        self.matrix_a_commitments.write_into(target);
+        self.matrix_b_commitments.write_into(target);
+        self.matrix_c_commitments.write_into(target);
+    }
+}
+
 // QUESTION: Currently using the utils hash_values function which uses quartic folding.
 // Is there any drawback to doing this here, where there's no layering?
 pub fn commit_polynomial_evaluations<
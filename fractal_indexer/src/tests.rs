@@ -1,9 +1,11 @@
 use std::vec;
 
-use crate::{index::*, *};
+use crate::{errors::IndexerError, index::*, *};
 use indexed_matrix::IndexedMatrix;
 use models::r1cs::Matrix;
 use models::{errors::R1CSError, r1cs::*};
+use snark_keys::generate_prover_and_verifier_keys;
+use winter_crypto::hashers::Blake3_256;
 use winter_math::{fields::f128::BaseElement, FieldElement, StarkField};
 
 type SmallFieldElement17 = fractal_math::smallprimefield::BaseElement<17, 3, 4>;
@@ -23,6 +25,7 @@ fn test_indexing() {
     let r1cs_instance = r1cs_instance_result.unwrap();
     let params = IndexParams::<BaseElement> {
         num_input_variables: 2,
+        num_public_inputs: 0,
         num_constraints: 2,
         num_non_zero: 4,
         max_degree: get_max_degree(2, 2, 4),
@@ -41,6 +44,7 @@ fn test_indexing() {
 fn test_domain_building_17() {
     let params = IndexParams::<SmallFieldElement17> {
         num_input_variables: 2,
+        num_public_inputs: 0,
         num_constraints: 2,
         num_non_zero: 4,
         max_degree: get_max_degree(2, 2, 4),
@@ -92,6 +96,7 @@ fn test_single_indexed_matrix_17() {
     let matrix_a = m1.unwrap();
     let params = IndexParams::<SmallFieldElement17> {
         num_input_variables: 2,
+        num_public_inputs: 0,
         num_constraints: 2,
         num_non_zero: 4,
         max_degree: get_max_degree(2, 2, 4),
@@ -130,6 +135,7 @@ fn test_indexing_f17() {
     let r1cs_instance = r1cs_instance_result.unwrap();
     let params = IndexParams::<SmallFieldElement17> {
         num_input_variables: 2,
+        num_public_inputs: 0,
         num_constraints: 2,
         num_non_zero: 4,
         max_degree: get_max_degree(2, 2, 4),
@@ -144,7 +150,209 @@ fn test_indexing_f17() {
     println!("Index is {:?}", index);
 }
 
+#[test]
+fn test_preprocessing_commitment_matches_verifier_key() {
+    let matrix_a = make_all_ones_matrix_f128("A", 2, 2).unwrap();
+    let matrix_b = make_all_ones_matrix_f128("B", 2, 2).unwrap();
+    let mut matrix_c = make_all_ones_matrix_f128("C", 2, 2).unwrap();
+    matrix_c.mat[1][0] = BaseElement::ZERO;
+    let r1cs_instance = R1CS::new(matrix_a, matrix_b, matrix_c).unwrap();
+    let params = IndexParams::<BaseElement> {
+        num_input_variables: 2,
+        num_public_inputs: 0,
+        num_constraints: 2,
+        num_non_zero: 4,
+        max_degree: get_max_degree(2, 2, 4),
+        eta: BaseElement::ONE,
+        eta_k: BaseElement::ONE,
+    };
+    let index = create_index_from_r1cs(params, r1cs_instance);
+    let (prover_key, verifier_key) =
+        generate_prover_and_verifier_keys::<Blake3_256<BaseElement>, BaseElement, 1>(index)
+            .unwrap();
+    assert_eq!(prover_key.preprocessing_commitment(), verifier_key.commitment());
+}
+
+#[test]
+fn test_eval_domain_size() {
+    let params = IndexParams::<BaseElement> {
+        num_input_variables: 2,
+        num_public_inputs: 0,
+        num_constraints: 2,
+        num_non_zero: 4,
+        max_degree: get_max_degree(2, 2, 4),
+        eta: BaseElement::ONE,
+        eta_k: BaseElement::ONE,
+    };
+    let domains = build_index_domains(params.clone());
+    assert_eq!(domains.l_field_len, eval_domain_size(params.max_degree, 4));
+    assert_eq!(eval_domain_size(params.max_degree, 8), 8 * params.max_degree);
+    assert_ne!(eval_domain_size(params.max_degree, 8), domains.l_field_len);
+}
+
+#[test]
+fn test_summing_domain_size_differing_sparsity() {
+    // A has 1, B has 3 and C has 9 non-zero entries
+    let matrix_a = make_all_ones_matrix_f128("A", 1, 1).unwrap();
+    let matrix_b = make_all_ones_matrix_f128("B", 1, 3).unwrap();
+    let matrix_c = make_all_ones_matrix_f128("C", 3, 3).unwrap();
+    assert_eq!(
+        required_summing_domain_size(&matrix_a, &matrix_b, &matrix_c),
+        16
+    );
+    assert_eq!(
+        required_summing_domain_size(&matrix_a, &matrix_b, &matrix_b),
+        4
+    );
+}
+
+#[test]
+#[should_panic(expected = "num_non_zero")]
+fn test_indexing_rejects_small_summing_domain() {
+    let matrix_a = make_all_ones_matrix_f128("A", 4, 4).unwrap();
+    let matrix_b = make_all_ones_matrix_f128("B", 4, 4).unwrap();
+    let mut matrix_c = make_all_ones_matrix_f128("C", 4, 4).unwrap();
+    matrix_c.mat[0][0] = BaseElement::ZERO;
+
+    let params = IndexParams::<BaseElement> {
+        num_input_variables: 4,
+        num_public_inputs: 0,
+        num_constraints: 4,
+        num_non_zero: 16,
+        max_degree: get_max_degree(4, 4, 16),
+        eta: BaseElement::ONE,
+        eta_k: BaseElement::ONE,
+    };
+    let domains = build_index_domains(params.clone());
+    let indexed_a = IndexedMatrix::new(&matrix_a, &domains);
+    let indexed_b = IndexedMatrix::new(&matrix_b, &domains);
+    let indexed_c = IndexedMatrix::new(&matrix_c, &domains);
+    let index = Index::new(params.clone(), indexed_a, indexed_b, indexed_c);
+    assert_eq!(index.params.num_non_zero, 16);
+
+    // A summing domain sized for the sparsest matrix, rather than the densest, is rejected.
+    let small_params = IndexParams::<BaseElement> {
+        num_non_zero: 8,
+        ..params
+    };
+    Index::new(
+        small_params,
+        IndexedMatrix::new(&matrix_a, &domains),
+        IndexedMatrix::new(&matrix_b, &domains),
+        IndexedMatrix::new(&matrix_c, &domains),
+    );
+}
+
+#[test]
+fn test_compute_max_degree() {
+    let sizes = [(8, 8, 16), (4, 32, 8), (64, 16, 4)];
+    for (num_input_variables, num_constraints, num_non_zero) in sizes {
+        let params =
+            IndexParams::<BaseElement>::new(num_input_variables, num_constraints, num_non_zero);
+        let max_degree = params.compute_max_degree();
+        assert_eq!(max_degree, params.max_degree);
+        assert!(max_degree.is_power_of_two());
+        // the e polynomials of the product and matrix sumchecks
+        let h_field_size = std::cmp::max(num_input_variables, num_constraints);
+        assert!(max_degree > h_field_size - 1);
+        assert!(max_degree > 2 * num_non_zero - 3);
+    }
+    assert_eq!(IndexParams::<BaseElement>::new(4, 32, 8).compute_max_degree(), 32);
+}
+
+#[test]
+fn test_index_params_try_new() {
+    let params = IndexParams::<BaseElement>::try_new(5, 6, 9).unwrap();
+    assert_eq!(
+        (params.num_input_variables, params.num_constraints, params.num_non_zero),
+        (8, 8, 16)
+    );
+    assert_eq!(params.checked_eval_domain_size(4).unwrap(), 4 * params.max_degree);
+
+    // H has to be a nontrivial subgroup that fits in the field
+    let too_large = 1 << (BaseElement::TWO_ADICITY + 1);
+    for (num_input_variables, num_constraints) in [(1, 1), (0, 4), (4, too_large)] {
+        assert!(matches!(
+            IndexParams::<BaseElement>::try_new(num_input_variables, num_constraints, 4),
+            Err(IndexerError::InvalidIndexParams(_))
+        ));
+    }
+    assert!(matches!(
+        IndexParams::<BaseElement>::try_new(4, 4, usize::MAX),
+        Err(IndexerError::InvalidIndexParams(_))
+    ));
+
+    // so does L
+    assert!(params.checked_eval_domain_size(3).is_err());
+    assert!(params.checked_eval_domain_size(1 << BaseElement::TWO_ADICITY).is_err());
+}
+
+#[test]
+fn test_try_new_index_rejects_small_summing_domain() {
+    let matrix_a = make_all_ones_matrix_f128("A", 4, 4).unwrap();
+    let params = IndexParams::<BaseElement>::new(4, 4, 16);
+    let domains = build_index_domains(params.clone());
+    let small_params = IndexParams::<BaseElement> {
+        num_non_zero: 8,
+        ..params
+    };
+    let index = Index::try_new(
+        small_params,
+        IndexedMatrix::new(&matrix_a, &domains),
+        IndexedMatrix::new(&matrix_a, &domains),
+        IndexedMatrix::new(&matrix_a, &domains),
+    );
+    assert!(matches!(index, Err(IndexerError::InvalidIndexParams(_))));
+}
+
+#[test]
+fn test_try_build_index_domains() {
+    let params = IndexParams::<BaseElement>::new(8, 8, 16);
+    let domains = try_build_index_domains_with_blowup(params.clone(), 8).unwrap();
+    assert_eq!(domains.l_field_len, 8 * params.max_degree);
+
+    let mut small_max_degree = params.clone();
+    small_max_degree.max_degree /= 2;
+    let mut not_power_of_two = params.clone();
+    not_power_of_two.num_constraints = 12;
+    for bad_params in [small_max_degree, not_power_of_two] {
+        assert!(matches!(
+            try_build_index_domains_with_blowup(bad_params, 4),
+            Err(IndexerError::InvalidIndexParams(_))
+        ));
+    }
+    for lde_blowup in [3, 1 << BaseElement::TWO_ADICITY] {
+        assert!(matches!(
+            try_build_index_domains_with_blowup(params.clone(), lde_blowup),
+            Err(IndexerError::InvalidIndexParams(_))
+        ));
+    }
+}
+
+#[test]
+#[should_panic(expected = "too small for this instance")]
+fn test_build_index_domains_rejects_small_max_degree() {
+    let mut params = IndexParams::<BaseElement>::new(8, 8, 16);
+    params.max_degree /= 2;
+    build_index_domains(params);
+}
+
 /// ***************  HELPERS *************** \\\
+#[test]
+fn test_coset_offsets() {
+    let h_size = 16;
+    let h_base = BaseElement::get_root_of_unity(4);
+    assert!(!is_valid_coset_offset(BaseElement::ONE, h_size));
+    assert!(!is_valid_coset_offset(h_base.exp(3), h_size));
+    // an element of a smaller subgroup is in H too
+    assert!(!is_valid_coset_offset(BaseElement::get_root_of_unity(2), h_size));
+    // but not in H if H is smaller than its order
+    assert!(is_valid_coset_offset(h_base, 8));
+
+    let eta = BaseElement::GENERATOR.exp(2 * BaseElement::TWO_ADICITY as u128);
+    assert!(is_valid_coset_offset(eta, h_size));
+}
+
 fn make_all_ones_matrix_f128(
     matrix_name: &str,
     rows: usize,
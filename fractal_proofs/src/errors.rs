@@ -0,0 +1,11 @@
+//! Errors produced while assembling proof objects.
+
+use displaydoc::Display;
+use thiserror::Error;
+
+/// Represents errors in building proof components
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum ProofError {
+    /// Got {0} queried evaluations but {1} Merkle proofs for them
+    OracleQueriesLengthMismatch(usize, usize),
+}
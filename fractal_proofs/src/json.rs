@@ -0,0 +1,541 @@
+//! JSON encoding of [FractalProof], for inspecting proofs with non-Rust tooling.
+//!
+//! Every proof struct is written field by field: sizes, degrees and positions are numbers,
+//! sub-proofs are nested objects, and only field elements and digests are hex strings (of their
+//! binary serialization), so they round-trip exactly. The one exception is winter-fri's
+//! [FriProof], whose layers are private to winter-fri and already stored as bytes, so it is
+//! written as the hex of its own serialization.
+
+use std::convert::TryFrom;
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use winter_crypto::{BatchMerkleProof, Hasher};
+use winter_fri::{FriOptions, FriProof};
+use winter_math::{FieldElement, StarkField};
+use winter_utils::{ByteReader, Deserializable, DeserializationError, Serializable, SliceReader};
+
+use crate::{
+    fri_options_from_parts, FieldTag, FractalProof, LincheckProof, LowDegreeProof, OracleQueries,
+    PhantomData, RowcheckProof, SumcheckProof,
+};
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> FractalProof<B, E, H> {
+    /// Encodes the proof as a JSON object with one entry per field of the proof.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("failed to encode proof as JSON")
+    }
+
+    /// Decodes a proof written by [FractalProof::to_json].
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+// Each proof struct is (de)serialized through a mirror struct with the same fields, in which
+// elements and digests are hex strings.
+macro_rules! impl_serde_via {
+    ($proof:ident, $json:ident) => {
+        impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serialize
+            for $proof<B, E, H>
+        {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $json::from(self).serialize(serializer)
+            }
+        }
+
+        impl<'de, B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Deserialize<'de>
+            for $proof<B, E, H>
+        {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $proof::try_from($json::deserialize(deserializer)?).map_err(D::Error::custom)
+            }
+        }
+    };
+}
+
+impl_serde_via!(FractalProof, FractalProofJson);
+impl_serde_via!(RowcheckProof, RowcheckProofJson);
+impl_serde_via!(LincheckProof, LincheckProofJson);
+impl_serde_via!(SumcheckProof, SumcheckProofJson);
+impl_serde_via!(LowDegreeProof, LowDegreeProofJson);
+impl_serde_via!(OracleQueries, OracleQueriesJson);
+
+// FRACTAL PROOF
+// ================================================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FractalProofJson {
+    field_tag: FieldTagJson,
+    pub_inputs_hash: String,
+    rowcheck_proof: RowcheckProofJson,
+    lincheck_a: LincheckProofJson,
+    lincheck_b: LincheckProofJson,
+    lincheck_c: LincheckProofJson,
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> From<&FractalProof<B, E, H>>
+    for FractalProofJson
+{
+    fn from(proof: &FractalProof<B, E, H>) -> Self {
+        FractalProofJson {
+            field_tag: FieldTagJson::from(&proof.field_tag),
+            pub_inputs_hash: to_hex(&proof.pub_inputs_hash),
+            rowcheck_proof: RowcheckProofJson::from(&proof.rowcheck_proof),
+            lincheck_a: LincheckProofJson::from(&proof.lincheck_a),
+            lincheck_b: LincheckProofJson::from(&proof.lincheck_b),
+            lincheck_c: LincheckProofJson::from(&proof.lincheck_c),
+        }
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> TryFrom<FractalProofJson>
+    for FractalProof<B, E, H>
+{
+    type Error = String;
+
+    fn try_from(json: FractalProofJson) -> Result<Self, String> {
+        Ok(FractalProof {
+            field_tag: FieldTag::try_from(json.field_tag)?,
+            pub_inputs_hash: from_hex("pub_inputs_hash", &json.pub_inputs_hash)?,
+            rowcheck_proof: RowcheckProof::try_from(json.rowcheck_proof)?,
+            lincheck_a: LincheckProof::try_from(json.lincheck_a)?,
+            lincheck_b: LincheckProof::try_from(json.lincheck_b)?,
+            lincheck_c: LincheckProof::try_from(json.lincheck_c)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldTagJson {
+    modulus: String,
+    extension_degree: usize,
+}
+
+impl From<&FieldTag> for FieldTagJson {
+    fn from(tag: &FieldTag) -> Self {
+        FieldTagJson {
+            modulus: bytes_to_hex(&tag.modulus),
+            extension_degree: tag.extension_degree,
+        }
+    }
+}
+
+impl TryFrom<FieldTagJson> for FieldTag {
+    type Error = String;
+
+    fn try_from(json: FieldTagJson) -> Result<Self, String> {
+        Ok(FieldTag {
+            modulus: hex_to_bytes(&json.modulus)
+                .ok_or_else(|| "field_tag.modulus is not valid hex".to_string())?,
+            extension_degree: json.extension_degree,
+        })
+    }
+}
+
+// ROWCHECK PROOF
+// ================================================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RowcheckProofJson {
+    options: FriOptionsJson,
+    num_evaluations: usize,
+    queried_positions: Vec<usize>,
+    f_eval_root: String,
+    f_az_evals: Vec<String>,
+    f_bz_evals: Vec<String>,
+    f_cz_evals: Vec<String>,
+    f_z_evals: Vec<String>,
+    f_eval_proof: BatchMerkleProofJson,
+    w_proof: LowDegreeProofJson,
+    s_eval_root: String,
+    s_original_evals: Vec<String>,
+    s_original_proof: BatchMerkleProofJson,
+    s_proof: String,
+    s_queried_evals: Vec<String>,
+    s_commitments: Vec<String>,
+    s_max_degree: usize,
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> From<&RowcheckProof<B, E, H>>
+    for RowcheckProofJson
+{
+    fn from(proof: &RowcheckProof<B, E, H>) -> Self {
+        RowcheckProofJson {
+            options: FriOptionsJson::from(&proof.options),
+            num_evaluations: proof.num_evaluations,
+            queried_positions: proof.queried_positions.clone(),
+            f_eval_root: to_hex(&proof.f_eval_root),
+            f_az_evals: to_hex_vec(&proof.f_az_evals),
+            f_bz_evals: to_hex_vec(&proof.f_bz_evals),
+            f_cz_evals: to_hex_vec(&proof.f_cz_evals),
+            f_z_evals: to_hex_vec(&proof.f_z_evals),
+            f_eval_proof: BatchMerkleProofJson::from(&proof.f_eval_proof),
+            w_proof: LowDegreeProofJson::from(&proof.w_proof),
+            s_eval_root: to_hex(&proof.s_eval_root),
+            s_original_evals: to_hex_vec(&proof.s_original_evals),
+            s_original_proof: BatchMerkleProofJson::from(&proof.s_original_proof),
+            s_proof: to_hex(&proof.s_proof),
+            s_queried_evals: to_hex_vec(&proof.s_queried_evals),
+            s_commitments: to_hex_vec(&proof.s_commitments),
+            s_max_degree: proof.s_max_degree,
+        }
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> TryFrom<RowcheckProofJson>
+    for RowcheckProof<B, E, H>
+{
+    type Error = String;
+
+    fn try_from(json: RowcheckProofJson) -> Result<Self, String> {
+        Ok(RowcheckProof {
+            options: FriOptions::try_from(json.options)?,
+            num_evaluations: json.num_evaluations,
+            queried_positions: json.queried_positions,
+            f_eval_root: from_hex("f_eval_root", &json.f_eval_root)?,
+            f_az_evals: from_hex_vec("f_az_evals", &json.f_az_evals)?,
+            f_bz_evals: from_hex_vec("f_bz_evals", &json.f_bz_evals)?,
+            f_cz_evals: from_hex_vec("f_cz_evals", &json.f_cz_evals)?,
+            f_z_evals: from_hex_vec("f_z_evals", &json.f_z_evals)?,
+            f_eval_proof: batch_merkle_proof_from_json(json.f_eval_proof)?,
+            w_proof: LowDegreeProof::try_from(json.w_proof)?,
+            s_eval_root: from_hex("s_eval_root", &json.s_eval_root)?,
+            s_original_evals: from_hex_vec("s_original_evals", &json.s_original_evals)?,
+            s_original_proof: batch_merkle_proof_from_json(json.s_original_proof)?,
+            s_proof: from_hex::<FriProof>("s_proof", &json.s_proof)?,
+            s_queried_evals: from_hex_vec("s_queried_evals", &json.s_queried_evals)?,
+            s_commitments: from_hex_vec("s_commitments", &json.s_commitments)?,
+            s_max_degree: json.s_max_degree,
+        })
+    }
+}
+
+// LINCHECK PROOF
+// ================================================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LincheckProofJson {
+    options: FriOptionsJson,
+    num_evaluations: usize,
+    alpha: String,
+    beta: String,
+    t_alpha_commitment: String,
+    t_alpha_queried: OracleQueriesJson,
+    products_sumcheck_proof: SumcheckProofJson,
+    gamma: String,
+    gamma_proof: LowDegreeProofJson,
+    row_queried: OracleQueriesJson,
+    col_queried: OracleQueriesJson,
+    val_queried: OracleQueriesJson,
+    matrix_sumcheck_proof: SumcheckProofJson,
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> From<&LincheckProof<B, E, H>>
+    for LincheckProofJson
+{
+    fn from(proof: &LincheckProof<B, E, H>) -> Self {
+        LincheckProofJson {
+            options: FriOptionsJson::from(&proof.options),
+            num_evaluations: proof.num_evaluations,
+            alpha: to_hex(&proof.alpha),
+            beta: to_hex(&proof.beta),
+            t_alpha_commitment: to_hex(&proof.t_alpha_commitment),
+            t_alpha_queried: OracleQueriesJson::from(&proof.t_alpha_queried),
+            products_sumcheck_proof: SumcheckProofJson::from(&proof.products_sumcheck_proof),
+            gamma: to_hex(&proof.gamma),
+            gamma_proof: LowDegreeProofJson::from(&proof.gamma_proof),
+            row_queried: OracleQueriesJson::from(&proof.row_queried),
+            col_queried: OracleQueriesJson::from(&proof.col_queried),
+            val_queried: OracleQueriesJson::from(&proof.val_queried),
+            matrix_sumcheck_proof: SumcheckProofJson::from(&proof.matrix_sumcheck_proof),
+        }
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> TryFrom<LincheckProofJson>
+    for LincheckProof<B, E, H>
+{
+    type Error = String;
+
+    fn try_from(json: LincheckProofJson) -> Result<Self, String> {
+        Ok(LincheckProof {
+            options: FriOptions::try_from(json.options)?,
+            num_evaluations: json.num_evaluations,
+            alpha: from_hex("alpha", &json.alpha)?,
+            beta: from_hex("beta", &json.beta)?,
+            t_alpha_commitment: from_hex("t_alpha_commitment", &json.t_alpha_commitment)?,
+            t_alpha_queried: OracleQueries::try_from(json.t_alpha_queried)?,
+            products_sumcheck_proof: SumcheckProof::try_from(json.products_sumcheck_proof)?,
+            gamma: from_hex("gamma", &json.gamma)?,
+            gamma_proof: LowDegreeProof::try_from(json.gamma_proof)?,
+            row_queried: OracleQueries::try_from(json.row_queried)?,
+            col_queried: OracleQueries::try_from(json.col_queried)?,
+            val_queried: OracleQueries::try_from(json.val_queried)?,
+            matrix_sumcheck_proof: SumcheckProof::try_from(json.matrix_sumcheck_proof)?,
+            _e: PhantomData,
+        })
+    }
+}
+
+// SUMCHECK PROOF
+// ================================================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SumcheckProofJson {
+    options: FriOptionsJson,
+    num_evaluations: usize,
+    queried_positions: Vec<usize>,
+    g_proof: LowDegreeProofJson,
+    g_max_degree: usize,
+    e_proof: LowDegreeProofJson,
+    e_max_degree: usize,
+    g_queried: OracleQueriesJson,
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> From<&SumcheckProof<B, E, H>>
+    for SumcheckProofJson
+{
+    fn from(proof: &SumcheckProof<B, E, H>) -> Self {
+        SumcheckProofJson {
+            options: FriOptionsJson::from(&proof.options),
+            num_evaluations: proof.num_evaluations,
+            queried_positions: proof.queried_positions.clone(),
+            g_proof: LowDegreeProofJson::from(&proof.g_proof),
+            g_max_degree: proof.g_max_degree,
+            e_proof: LowDegreeProofJson::from(&proof.e_proof),
+            e_max_degree: proof.e_max_degree,
+            g_queried: OracleQueriesJson::from(&proof.g_queried),
+        }
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> TryFrom<SumcheckProofJson>
+    for SumcheckProof<B, E, H>
+{
+    type Error = String;
+
+    fn try_from(json: SumcheckProofJson) -> Result<Self, String> {
+        Ok(SumcheckProof {
+            options: FriOptions::try_from(json.options)?,
+            num_evaluations: json.num_evaluations,
+            queried_positions: json.queried_positions,
+            g_proof: LowDegreeProof::try_from(json.g_proof)?,
+            g_max_degree: json.g_max_degree,
+            e_proof: LowDegreeProof::try_from(json.e_proof)?,
+            e_max_degree: json.e_max_degree,
+            g_queried: OracleQueries::try_from(json.g_queried)?,
+        })
+    }
+}
+
+// LOW DEGREE PROOF
+// ================================================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LowDegreeProofJson {
+    options: FriOptionsJson,
+    num_evaluations: usize,
+    queried_positions: Vec<usize>,
+    unpadded_queried_evaluations: Vec<String>,
+    padded_queried_evaluations: Vec<String>,
+    commitments: Vec<String>,
+    tree_root: String,
+    tree_proof: BatchMerkleProofJson,
+    fri_proof: String,
+    max_degree: usize,
+    fri_max_degree: usize,
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> From<&LowDegreeProof<B, E, H>>
+    for LowDegreeProofJson
+{
+    fn from(proof: &LowDegreeProof<B, E, H>) -> Self {
+        LowDegreeProofJson {
+            options: FriOptionsJson::from(&proof.options),
+            num_evaluations: proof.num_evaluations,
+            queried_positions: proof.queried_positions.clone(),
+            unpadded_queried_evaluations: to_hex_vec(&proof.unpadded_queried_evaluations),
+            padded_queried_evaluations: to_hex_vec(&proof.padded_queried_evaluations),
+            commitments: to_hex_vec(&proof.commitments),
+            tree_root: to_hex(&proof.tree_root),
+            tree_proof: BatchMerkleProofJson::from(&proof.tree_proof),
+            fri_proof: to_hex(&proof.fri_proof),
+            max_degree: proof.max_degree,
+            fri_max_degree: proof.fri_max_degree,
+        }
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> TryFrom<LowDegreeProofJson>
+    for LowDegreeProof<B, E, H>
+{
+    type Error = String;
+
+    fn try_from(json: LowDegreeProofJson) -> Result<Self, String> {
+        Ok(LowDegreeProof {
+            options: FriOptions::try_from(json.options)?,
+            num_evaluations: json.num_evaluations,
+            queried_positions: json.queried_positions,
+            unpadded_queried_evaluations: from_hex_vec(
+                "unpadded_queried_evaluations",
+                &json.unpadded_queried_evaluations,
+            )?,
+            padded_queried_evaluations: from_hex_vec(
+                "padded_queried_evaluations",
+                &json.padded_queried_evaluations,
+            )?,
+            commitments: from_hex_vec("commitments", &json.commitments)?,
+            tree_root: from_hex("tree_root", &json.tree_root)?,
+            tree_proof: batch_merkle_proof_from_json(json.tree_proof)?,
+            fri_proof: from_hex::<FriProof>("fri_proof", &json.fri_proof)?,
+            max_degree: json.max_degree,
+            fri_max_degree: json.fri_max_degree,
+        })
+    }
+}
+
+// ORACLE QUERIES
+// ================================================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OracleQueriesJson {
+    queried_evals: Vec<String>,
+    queried_proofs: Vec<Vec<String>>,
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> From<&OracleQueries<B, E, H>>
+    for OracleQueriesJson
+{
+    fn from(queries: &OracleQueries<B, E, H>) -> Self {
+        OracleQueriesJson {
+            queried_evals: to_hex_vec(&queries.queried_evals),
+            queried_proofs: queries.queried_proofs.iter().map(|path| to_hex_vec(path)).collect(),
+        }
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> TryFrom<OracleQueriesJson>
+    for OracleQueries<B, E, H>
+{
+    type Error = String;
+
+    fn try_from(json: OracleQueriesJson) -> Result<Self, String> {
+        let queried_proofs = json
+            .queried_proofs
+            .iter()
+            .map(|path| from_hex_vec("queried_proofs", path))
+            .collect::<Result<_, _>>()?;
+        OracleQueries::new(from_hex_vec("queried_evals", &json.queried_evals)?, queried_proofs)
+            .map_err(|err| err.to_string())
+    }
+}
+
+// FRI OPTIONS AND MERKLE PROOFS
+// ================================================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FriOptionsJson {
+    blowup_factor: usize,
+    folding_factor: usize,
+    max_remainder_size: usize,
+}
+
+impl From<&FriOptions> for FriOptionsJson {
+    fn from(options: &FriOptions) -> Self {
+        FriOptionsJson {
+            blowup_factor: options.blowup_factor(),
+            folding_factor: options.folding_factor(),
+            max_remainder_size: options.max_remainder_size(),
+        }
+    }
+}
+
+impl TryFrom<FriOptionsJson> for FriOptions {
+    type Error = String;
+
+    fn try_from(json: FriOptionsJson) -> Result<Self, String> {
+        fri_options_from_parts(json.blowup_factor, json.folding_factor, json.max_remainder_size)
+            .map_err(|err| format!("options: {}", err))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchMerkleProofJson {
+    leaves: Vec<String>,
+    nodes: Vec<Vec<String>>,
+    depth: u8,
+}
+
+impl<H: Hasher> From<&BatchMerkleProof<H>> for BatchMerkleProofJson {
+    fn from(proof: &BatchMerkleProof<H>) -> Self {
+        BatchMerkleProofJson {
+            leaves: to_hex_vec(&proof.leaves),
+            nodes: proof.nodes.iter().map(|nodes| to_hex_vec(nodes)).collect(),
+            depth: proof.depth,
+        }
+    }
+}
+
+// BatchMerkleProof is winter-crypto's, so this can't be a TryFrom impl.
+fn batch_merkle_proof_from_json<H: Hasher>(
+    json: BatchMerkleProofJson,
+) -> Result<BatchMerkleProof<H>, String> {
+    Ok(BatchMerkleProof {
+        leaves: from_hex_vec("leaves", &json.leaves)?,
+        nodes: json
+            .nodes
+            .iter()
+            .map(|nodes| from_hex_vec("nodes", nodes))
+            .collect::<Result<_, _>>()?,
+        depth: json.depth,
+    })
+}
+
+// HEX HELPERS
+// ================================================================================================
+
+fn to_hex<T: Serializable>(value: &T) -> String {
+    bytes_to_hex(&value.to_bytes())
+}
+
+fn to_hex_vec<T: Serializable>(values: &[T]) -> Vec<String> {
+    values.iter().map(to_hex).collect()
+}
+
+// Every byte of a value has to be consumed, otherwise it wasn't written by `to_json`.
+fn from_hex<T: Deserializable>(name: &str, hex: &str) -> Result<T, String> {
+    let bytes = hex_to_bytes(hex).ok_or_else(|| format!("{} is not valid hex", name))?;
+    let mut reader = SliceReader::new(&bytes);
+    let value = T::read_from(&mut reader).map_err(|err| format!("{}: {}", name, err))?;
+    if reader.has_more_bytes() {
+        return Err(format!("{}: {}", name, DeserializationError::UnconsumedBytes));
+    }
+    Ok(value)
+}
+
+fn from_hex_vec<T: Deserializable>(name: &str, hexes: &[String]) -> Result<Vec<T>, String> {
+    hexes.iter().map(|hex| from_hex(name, hex)).collect()
+}
+
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [hi, lo] => u8::from_str_radix(std::str::from_utf8(&[*hi, *lo]).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
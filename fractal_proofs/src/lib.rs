@@ -1,17 +1,27 @@
+pub mod errors;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(test)]
 mod tests;
 
 pub use std::convert::TryInto;
 use std::{marker::PhantomData, usize};
 
+use errors::ProofError;
+
 pub use fractal_utils::{errors::MatrixError, matrix_utils::*, polynomial_utils::*, *};
 use winter_crypto::{Hasher, BatchMerkleProof};
 pub use winter_fri::{DefaultProverChannel, FriOptions, FriProof};
 pub use winter_math::{fft, fields::f128::BaseElement, FieldElement, StarkField, *};
 pub use winter_utils::{
-    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable,
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
 };
 
 pub struct FractalProof<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> {
+    // The fields the proof was generated over
+    pub field_tag: FieldTag,
+    // Hash of the public input bytes the prover seeded its transcript with
+    pub pub_inputs_hash: H::Digest,
     pub rowcheck_proof: RowcheckProof<B, E, H>,
     pub lincheck_a: LincheckProof<B, E, H>,
     pub lincheck_b: LincheckProof<B, E, H>,
@@ -23,6 +33,8 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serializable
 {
     /// Serializes `self` and writes the resulting bytes into the `target` writer.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.field_tag.write_into(target);
+        self.pub_inputs_hash.write_into(target);
         self.rowcheck_proof.write_into(target);
         self.lincheck_a.write_into(target);
         self.lincheck_b.write_into(target);
@@ -30,10 +42,121 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serializable
     }
 }
 
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Deserializable
+    for FractalProof<B, E, H>
+{
+    /// Reads a proof written by `write_into` from the `source` reader.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(FractalProof {
+            field_tag: FieldTag::read_from(source)?,
+            pub_inputs_hash: H::Digest::read_from(source)?,
+            rowcheck_proof: RowcheckProof::read_from(source)?,
+            lincheck_a: LincheckProof::read_from(source)?,
+            lincheck_b: LincheckProof::read_from(source)?,
+            lincheck_c: LincheckProof::read_from(source)?,
+        })
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> FractalProof<B, E, H> {
+    /// Serialized size in bytes of each part of the proof.
+    pub fn size_breakdown(&self) -> ProofSizeReport {
+        ProofSizeReport {
+            field_tag: self.field_tag.to_bytes().len(),
+            pub_inputs_hash: self.pub_inputs_hash.to_bytes().len(),
+            rowcheck: self.rowcheck_proof.to_bytes().len(),
+            lincheck_a: self.lincheck_a.to_bytes().len(),
+            lincheck_b: self.lincheck_b.to_bytes().len(),
+            lincheck_c: self.lincheck_c.to_bytes().len(),
+        }
+    }
+}
+
+/// Byte counts of the parts of a serialized [FractalProof], see [FractalProof::size_breakdown].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofSizeReport {
+    pub field_tag: usize,
+    pub pub_inputs_hash: usize,
+    pub rowcheck: usize,
+    pub lincheck_a: usize,
+    pub lincheck_b: usize,
+    pub lincheck_c: usize,
+}
+
+impl ProofSizeReport {
+    /// Size of the whole serialized proof.
+    pub fn total(&self) -> usize {
+        self.field_tag + self.pub_inputs_hash + self.rowcheck + self.lincheck_a + self.lincheck_b + self.lincheck_c
+    }
+}
+
+/// Identifies the fields a proof was generated over: the modulus of the base field and the
+/// degree of the extension the challenges are drawn from. A proof can only be read and
+/// verified with the same field configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldTag {
+    pub modulus: Vec<u8>,
+    pub extension_degree: usize,
+}
+
+impl FieldTag {
+    pub fn of<B: StarkField, E: FieldElement<BaseField = B>>() -> Self {
+        FieldTag {
+            modulus: B::get_modulus_le_bytes(),
+            extension_degree: E::ELEMENT_BYTES / B::ELEMENT_BYTES,
+        }
+    }
+}
+
+impl Serializable for FieldTag {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(self.modulus.len() as u8);
+        target.write_u8_slice(&self.modulus);
+        target.write_u8(self.extension_degree as u8);
+    }
+}
+
+impl Deserializable for FieldTag {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let modulus_len = source.read_u8()? as usize;
+        Ok(FieldTag {
+            modulus: source.read_u8_vec(modulus_len)?,
+            extension_degree: source.read_u8()? as usize,
+        })
+    }
+}
+
+/// The FRI options and number of queries a verifier accepts proofs for. A proof carries its own
+/// copy of the FRI options and as many queries as the prover chose, so verifiers check those
+/// against these rather than trusting them.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProofOptions {
+    pub fri_options: FriOptions,
+    pub num_queries: usize,
+}
+
+impl ProofOptions {
+    pub fn new(fri_options: FriOptions, num_queries: usize) -> Self {
+        ProofOptions {
+            fri_options,
+            num_queries,
+        }
+    }
+}
+
 pub struct RowcheckProof<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> {
     pub options: FriOptions,
     pub num_evaluations: usize,
     pub queried_positions: Vec<usize>,
+    /// Commitment to f_Az, f_Bz, f_Cz and f_z over the evaluation domain, one leaf per point
+    pub f_eval_root: H::Digest,
+    pub f_az_evals: Vec<E>,
+    pub f_bz_evals: Vec<E>,
+    pub f_cz_evals: Vec<E>,
+    pub f_z_evals: Vec<E>,
+    pub f_eval_proof: BatchMerkleProof<H>,
+    /// Shows w = (f_z - x_hat) / v_X has low degree, i.e. that z starts with the public inputs
+    pub w_proof: LowDegreeProof<B, E, H>,
     pub s_eval_root: H::Digest,
     pub s_original_evals: Vec<E>,
     pub s_original_proof: BatchMerkleProof<H>,
@@ -48,15 +171,50 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serializable
 {
     /// Serializes `self` and writes the resulting bytes into the `target` writer.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        target.write_u8(self.num_evaluations as u8);
-        target.write_u8(self.queried_positions.len() as u8);
-        for pos in 0..self.queried_positions.len() {
-            target.write_u8(self.queried_positions[pos] as u8);
-        }
+        write_fri_options(&self.options, target);
+        target.write_u32(self.num_evaluations as u32);
+        write_positions(&self.queried_positions, target);
+        self.f_eval_root.write_into(target);
+        write_vec(&self.f_az_evals, target);
+        write_vec(&self.f_bz_evals, target);
+        write_vec(&self.f_cz_evals, target);
+        write_vec(&self.f_z_evals, target);
+        write_batch_merkle_proof(&self.f_eval_proof, target);
+        self.w_proof.write_into(target);
+        self.s_eval_root.write_into(target);
+        write_vec(&self.s_original_evals, target);
+        write_batch_merkle_proof(&self.s_original_proof, target);
         self.s_proof.write_into(target);
-        self.s_queried_evals.write_into(target);
-        self.s_commitments.write_into(target);
-        target.write_u8(self.s_max_degree as u8);
+        write_vec(&self.s_queried_evals, target);
+        write_vec(&self.s_commitments, target);
+        target.write_u32(self.s_max_degree as u32);
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Deserializable
+    for RowcheckProof<B, E, H>
+{
+    /// Reads a proof written by `write_into` from the `source` reader.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(RowcheckProof {
+            options: read_fri_options(source)?,
+            num_evaluations: source.read_u32()? as usize,
+            queried_positions: read_positions(source)?,
+            f_eval_root: H::Digest::read_from(source)?,
+            f_az_evals: read_vec(source)?,
+            f_bz_evals: read_vec(source)?,
+            f_cz_evals: read_vec(source)?,
+            f_z_evals: read_vec(source)?,
+            f_eval_proof: read_batch_merkle_proof(source)?,
+            w_proof: LowDegreeProof::read_from(source)?,
+            s_eval_root: H::Digest::read_from(source)?,
+            s_original_evals: read_vec(source)?,
+            s_original_proof: read_batch_merkle_proof(source)?,
+            s_proof: FriProof::read_from(source)?,
+            s_queried_evals: read_vec(source)?,
+            s_commitments: read_vec(source)?,
+            s_max_degree: source.read_u32()? as usize,
+        })
     }
 }
 
@@ -70,26 +228,42 @@ pub struct SumcheckProof<B: StarkField, E: FieldElement<BaseField = B>, H: Hashe
     pub g_max_degree: usize,
     pub e_proof: LowDegreeProof<B,E,H>,
     pub e_max_degree: usize,
+    /// g opened at the positions the e proof queries, against g_proof.tree_root. The verifier
+    /// checks the sumcheck identity at these positions.
+    pub g_queried: OracleQueries<B, E, H>,
 }
 
-// TODO: FIX once interface is stable
 impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serializable
     for SumcheckProof<B, E, H>
 {
     /// Serializes `self` and writes the resulting bytes into the `target` writer.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        target.write_u8(self.num_evaluations as u8);
-        /*target.write_u8(self.queried_positions.len() as u8);
-        for pos in 0..self.queried_positions.len() {
-            target.write_u8(self.queried_positions[pos] as u8);
-        }
+        write_fri_options(&self.options, target);
+        target.write_u32(self.num_evaluations as u32);
+        write_positions(&self.queried_positions, target);
         self.g_proof.write_into(target);
+        target.write_u32(self.g_max_degree as u32);
+        self.e_proof.write_into(target);
+        target.write_u32(self.e_max_degree as u32);
         self.g_queried.write_into(target);
-        target.write_u8(self.g_max_degree as u8);
+    }
+}
 
-        self.e_proof.write_into(target);
-        self.e_queried.write_into(target);
-        target.write_u8(self.e_max_degree as u8);*/
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Deserializable
+    for SumcheckProof<B, E, H>
+{
+    /// Reads a proof written by `write_into` from the `source` reader.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(SumcheckProof {
+            options: read_fri_options(source)?,
+            num_evaluations: source.read_u32()? as usize,
+            queried_positions: read_positions(source)?,
+            g_proof: LowDegreeProof::read_from(source)?,
+            g_max_degree: source.read_u32()? as usize,
+            e_proof: LowDegreeProof::read_from(source)?,
+            e_max_degree: source.read_u32()? as usize,
+            g_queried: OracleQueries::read_from(source)?,
+        })
     }
 }
 
@@ -102,6 +276,9 @@ pub struct LincheckProof<B: StarkField, E: FieldElement<BaseField = B>, H: Hashe
     pub t_alpha_queried: OracleQueries<B, E, H>,
     pub products_sumcheck_proof: SumcheckProof<B, E, H>,
     pub gamma: B,
+    /// Shows (t_alpha - gamma) / (X - beta) has low degree, i.e. gamma = t_alpha(beta). t_alpha
+    /// is opened at the positions this proof queries.
+    pub gamma_proof: LowDegreeProof<B, E, H>,
     pub row_queried: OracleQueries<B, E, H>,
     pub col_queried: OracleQueries<B, E, H>,
     pub val_queried: OracleQueries<B, E, H>,
@@ -114,13 +291,15 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serializable
 {
     /// Serializes `self` and writes the resulting bytes into the `target` writer.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        target.write_u8(self.num_evaluations as u8);
+        write_fri_options(&self.options, target);
+        target.write_u32(self.num_evaluations as u32);
         self.alpha.write_into(target);
         self.beta.write_into(target);
         self.t_alpha_commitment.write_into(target);
         self.t_alpha_queried.write_into(target);
         self.products_sumcheck_proof.write_into(target);
         self.gamma.write_into(target);
+        self.gamma_proof.write_into(target);
         self.row_queried.write_into(target);
         self.col_queried.write_into(target);
         self.val_queried.write_into(target);
@@ -128,18 +307,51 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serializable
     }
 }
 
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Deserializable
+    for LincheckProof<B, E, H>
+{
+    /// Reads a proof written by `write_into` from the `source` reader.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(LincheckProof {
+            options: read_fri_options(source)?,
+            num_evaluations: source.read_u32()? as usize,
+            alpha: B::read_from(source)?,
+            beta: B::read_from(source)?,
+            t_alpha_commitment: H::Digest::read_from(source)?,
+            t_alpha_queried: OracleQueries::read_from(source)?,
+            products_sumcheck_proof: SumcheckProof::read_from(source)?,
+            gamma: B::read_from(source)?,
+            gamma_proof: LowDegreeProof::read_from(source)?,
+            row_queried: OracleQueries::read_from(source)?,
+            col_queried: OracleQueries::read_from(source)?,
+            val_queried: OracleQueries::read_from(source)?,
+            matrix_sumcheck_proof: SumcheckProof::read_from(source)?,
+            _e: PhantomData,
+        })
+    }
+}
+
 pub struct OracleQueries<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> {
     pub queried_evals: Vec<E>,
     pub queried_proofs: Vec<Vec<H::Digest>>,
 }
 
-// FIXME: change this to return a Result and throw an error if qeuried_evals.len() != queried_proofs.len()
 impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> OracleQueries<B, E, H> {
-    pub fn new(queried_evals: Vec<E>, queried_proofs: Vec<Vec<H::Digest>>) -> Self {
-        OracleQueries {
+    /// Each queried evaluation needs exactly one Merkle proof.
+    pub fn new(
+        queried_evals: Vec<E>,
+        queried_proofs: Vec<Vec<H::Digest>>,
+    ) -> Result<Self, ProofError> {
+        if queried_evals.len() != queried_proofs.len() {
+            return Err(ProofError::OracleQueriesLengthMismatch(
+                queried_evals.len(),
+                queried_proofs.len(),
+            ));
+        }
+        Ok(OracleQueries {
             queried_evals,
             queried_proofs,
-        }
+        })
     }
 }
 
@@ -148,8 +360,27 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serializable
 {
     /// Serializes `self` and writes the resulting bytes into the `target` writer.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        self.queried_evals.write_into(target);
-        self.queried_proofs.write_into(target);
+        write_vec(&self.queried_evals, target);
+        target.write_u32(self.queried_proofs.len() as u32);
+        for path in self.queried_proofs.iter() {
+            write_vec(path, target);
+        }
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Deserializable
+    for OracleQueries<B, E, H>
+{
+    /// Reads a proof written by `write_into` from the `source` reader.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let queried_evals = read_vec(source)?;
+        let num_paths = source.read_u32()? as usize;
+        let mut queried_proofs = Vec::new();
+        for _ in 0..num_paths {
+            queried_proofs.push(read_vec(source)?);
+        }
+        OracleQueries::new(queried_evals, queried_proofs)
+            .map_err(|err| DeserializationError::InvalidValue(err.to_string()))
     }
 }
 
@@ -166,19 +397,159 @@ pub struct LowDegreeProof<B: StarkField, E: FieldElement<BaseField = B>, H: Hash
     pub max_degree: usize,
     pub fri_max_degree: usize,
 }
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> LowDegreeProof<B, E, H> {
+    /// Evaluations of the polynomial FRI folded down to, as sent in the FRI proof.
+    pub fn remainder(&self) -> Result<Vec<E>, DeserializationError> {
+        self.fri_proof.parse_remainder()
+    }
+
+    /// Degree of the FRI remainder polynomial. Like the FRI verifier, this interpolates the
+    /// remainder over an unshifted domain of its own size. For an honest proof this is below
+    /// `(fri_max_degree + 1) / folding_factor^num_layers`.
+    pub fn remainder_degree(&self) -> Result<usize, DeserializationError> {
+        let mut remainder = self.remainder()?;
+        let inv_twiddles = fft::get_inv_twiddles::<B>(remainder.len());
+        fft::interpolate_poly(&mut remainder, &inv_twiddles);
+        Ok(polynom::degree_of(&remainder))
+    }
+}
+
 // TODO: fix once interface is finalized (should this just be a serde macro?)
 impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Serializable
     for LowDegreeProof<B, E, H>
 {
     /// Serializes `self` and writes the resulting bytes into the `target` writer.
     fn write_into<W: ByteWriter>(&self, target: &mut W) {
-        target.write_u8(self.num_evaluations as u8);
-        target.write_u8(self.queried_positions.len() as u8);
-        for pos in 0..self.queried_positions.len() {
-            target.write_u8(self.queried_positions[pos] as u8);
-        }
+        write_fri_options(&self.options, target);
+        target.write_u32(self.num_evaluations as u32);
+        write_positions(&self.queried_positions, target);
+        write_vec(&self.unpadded_queried_evaluations, target);
+        write_vec(&self.padded_queried_evaluations, target);
+        write_vec(&self.commitments, target);
+        self.tree_root.write_into(target);
+        write_batch_merkle_proof(&self.tree_proof, target);
         self.fri_proof.write_into(target);
-        //self.queried.write_into(target);
-        target.write_u8(self.max_degree as u8);
+        target.write_u32(self.max_degree as u32);
+        target.write_u32(self.fri_max_degree as u32);
+    }
+}
+
+impl<B: StarkField, E: FieldElement<BaseField = B>, H: Hasher> Deserializable
+    for LowDegreeProof<B, E, H>
+{
+    /// Reads a proof written by `write_into` from the `source` reader.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        Ok(LowDegreeProof {
+            options: read_fri_options(source)?,
+            num_evaluations: source.read_u32()? as usize,
+            queried_positions: read_positions(source)?,
+            unpadded_queried_evaluations: read_vec(source)?,
+            padded_queried_evaluations: read_vec(source)?,
+            commitments: read_vec(source)?,
+            tree_root: H::Digest::read_from(source)?,
+            tree_proof: read_batch_merkle_proof(source)?,
+            fri_proof: FriProof::read_from(source)?,
+            max_degree: source.read_u32()? as usize,
+            fri_max_degree: source.read_u32()? as usize,
+        })
+    }
+}
+
+// SERIALIZATION HELPERS
+// ================================================================================================
+
+// Vec<T> is written without its length, so every vector in a proof is prefixed with it.
+fn write_vec<T: Serializable, W: ByteWriter>(values: &[T], target: &mut W) {
+    target.write_u32(values.len() as u32);
+    for value in values.iter() {
+        value.write_into(target);
     }
-}
\ No newline at end of file
+}
+
+// Length prefixes come from untrusted bytes and ByteReader can't say how many bytes are left, so
+// readers never preallocate from them: vectors grow one element at a time, and a prefix longer
+// than the input fails with UnexpectedEOF once the bytes run out.
+fn read_vec<T: Deserializable, R: ByteReader>(
+    source: &mut R,
+) -> Result<Vec<T>, DeserializationError> {
+    let len = source.read_u32()? as usize;
+    let mut values = Vec::new();
+    for _ in 0..len {
+        values.push(T::read_from(source)?);
+    }
+    Ok(values)
+}
+
+fn write_positions<W: ByteWriter>(positions: &[usize], target: &mut W) {
+    target.write_u32(positions.len() as u32);
+    for &pos in positions.iter() {
+        target.write_u32(pos as u32);
+    }
+}
+
+fn read_positions<R: ByteReader>(source: &mut R) -> Result<Vec<usize>, DeserializationError> {
+    let len = source.read_u32()? as usize;
+    let mut positions = Vec::new();
+    for _ in 0..len {
+        positions.push(source.read_u32()? as usize);
+    }
+    Ok(positions)
+}
+
+fn write_fri_options<W: ByteWriter>(options: &FriOptions, target: &mut W) {
+    target.write_u32(options.blowup_factor() as u32);
+    target.write_u32(options.folding_factor() as u32);
+    target.write_u32(options.max_remainder_size() as u32);
+}
+
+fn read_fri_options<R: ByteReader>(source: &mut R) -> Result<FriOptions, DeserializationError> {
+    let blowup_factor = source.read_u32()? as usize;
+    let folding_factor = source.read_u32()? as usize;
+    let max_remainder_size = source.read_u32()? as usize;
+    fri_options_from_parts(blowup_factor, folding_factor, max_remainder_size)
+}
+
+fn fri_options_from_parts(
+    blowup_factor: usize,
+    folding_factor: usize,
+    max_remainder_size: usize,
+) -> Result<FriOptions, DeserializationError> {
+    // FriOptions::new panics on values FRI doesn't support
+    if !blowup_factor.is_power_of_two()
+        || ![4, 8, 16].contains(&folding_factor)
+        || max_remainder_size < 2 * folding_factor
+    {
+        return Err(DeserializationError::InvalidValue(format!(
+            "unsupported FRI options: blowup {}, folding {}, max remainder {}",
+            blowup_factor, folding_factor, max_remainder_size
+        )));
+    }
+    Ok(FriOptions::new(blowup_factor, folding_factor, max_remainder_size))
+}
+
+fn write_batch_merkle_proof<H: Hasher, W: ByteWriter>(proof: &BatchMerkleProof<H>, target: &mut W) {
+    write_vec(&proof.leaves, target);
+    target.write_u32(proof.nodes.len() as u32);
+    for nodes in proof.nodes.iter() {
+        write_vec(nodes, target);
+    }
+    target.write_u8(proof.depth);
+}
+
+fn read_batch_merkle_proof<H: Hasher, R: ByteReader>(
+    source: &mut R,
+) -> Result<BatchMerkleProof<H>, DeserializationError> {
+    let leaves = read_vec(source)?;
+    let num_nodes = source.read_u32()? as usize;
+    let mut nodes = Vec::new();
+    for _ in 0..num_nodes {
+        nodes.push(read_vec(source)?);
+    }
+    let depth = source.read_u8()?;
+    Ok(BatchMerkleProof {
+        leaves,
+        nodes,
+        depth,
+    })
+}
@@ -1 +1,88 @@
+use crate::errors::ProofError;
+use crate::{
+    BaseElement, ByteWriter, Deserializable, DeserializationError, FieldElement, FriOptions,
+    LowDegreeProof, OracleQueries, Serializable, SliceReader,
+};
+use winter_crypto::{hashers::Blake3_256, Hasher};
 
+type H = Blake3_256<BaseElement>;
+
+#[test]
+fn test_oracle_queries_lengths() {
+    let evals = vec![BaseElement::ONE, BaseElement::ZERO];
+    let proof = vec![H::hash(&[1u8])];
+
+    let queries = OracleQueries::<BaseElement, BaseElement, H>::new(
+        evals.clone(),
+        vec![proof.clone(), proof.clone()],
+    )
+    .unwrap();
+    assert_eq!(queries.queried_evals, evals);
+
+    assert_eq!(
+        OracleQueries::<BaseElement, BaseElement, H>::new(evals, vec![proof]).err(),
+        Some(ProofError::OracleQueriesLengthMismatch(2, 1))
+    );
+}
+
+#[test]
+fn test_oracle_queries_round_trip() {
+    let evals = vec![BaseElement::ONE, BaseElement::new(7)];
+    let paths = vec![vec![H::hash(&[1u8]), H::hash(&[2u8])], vec![H::hash(&[3u8])]];
+    let queries =
+        OracleQueries::<BaseElement, BaseElement, H>::new(evals.clone(), paths.clone()).unwrap();
+    let bytes = queries.to_bytes();
+
+    let mut reader = SliceReader::new(&bytes);
+    let read = OracleQueries::<BaseElement, BaseElement, H>::read_from(&mut reader).unwrap();
+    assert_eq!(read.queried_evals, evals);
+    assert_eq!(read.queried_proofs, paths);
+
+    // drop the second path but keep both evaluations
+    let mut truncated = OracleQueries::<BaseElement, BaseElement, H>::new(evals, paths).unwrap();
+    truncated.queried_proofs.pop();
+    let bytes = truncated.to_bytes();
+    assert!(matches!(
+        OracleQueries::<BaseElement, BaseElement, H>::read_from(&mut SliceReader::new(&bytes)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn test_huge_length_prefix() {
+    // evaluations claimed to be u32::MAX long
+    let mut bytes = Vec::new();
+    bytes.write_u32(u32::MAX);
+    BaseElement::ONE.write_into(&mut bytes);
+    assert_eq!(
+        OracleQueries::<BaseElement, BaseElement, H>::read_from(&mut SliceReader::new(&bytes))
+            .err(),
+        Some(DeserializationError::UnexpectedEOF)
+    );
+
+    // one valid evaluation, then u32::MAX paths
+    let mut bytes = Vec::new();
+    bytes.write_u32(1);
+    BaseElement::ONE.write_into(&mut bytes);
+    bytes.write_u32(u32::MAX);
+    assert_eq!(
+        OracleQueries::<BaseElement, BaseElement, H>::read_from(&mut SliceReader::new(&bytes))
+            .err(),
+        Some(DeserializationError::UnexpectedEOF)
+    );
+
+    // a low degree proof claiming u32::MAX queried positions
+    let options = FriOptions::new(4, 4, 32);
+    let mut bytes = Vec::new();
+    bytes.write_u32(options.blowup_factor() as u32);
+    bytes.write_u32(options.folding_factor() as u32);
+    bytes.write_u32(options.max_remainder_size() as u32);
+    bytes.write_u32(64);
+    bytes.write_u32(u32::MAX);
+    bytes.write_u32(3);
+    assert_eq!(
+        LowDegreeProof::<BaseElement, BaseElement, H>::read_from(&mut SliceReader::new(&bytes))
+            .err(),
+        Some(DeserializationError::UnexpectedEOF)
+    );
+}
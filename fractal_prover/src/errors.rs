@@ -2,8 +2,12 @@
 
 use core::fmt;
 
-use winter_crypto::MerkleTreeError;
+use winter_crypto::{MerkleTreeError, RandomCoinError};
 use displaydoc::Display;
+use fractal_indexer::errors::IndexerError;
+use fractal_proofs::errors::ProofError;
+use fractal_sumcheck::errors::SumcheckProverError;
+use low_degree::errors::LowDegreeProverError;
 use models::errors::R1CSError;
 use thiserror::Error;
 
@@ -13,6 +17,12 @@ pub enum ProverError {
     R1CSErr(R1CSError),
     InvalidMatrixName(String),
     MerkleTreeErr(MerkleTreeError),
+    InvalidFriOptions(String),
+    IndexerErr(IndexerError),
+    InvalidPublicInputs(String),
+    InvalidAssignment(String),
+    LowDegreeProverErr(LowDegreeProverError),
+    RandomCoinErr(RandomCoinError),
 }
 
 impl From<LincheckError> for ProverError {
@@ -27,17 +37,74 @@ impl From<R1CSError> for ProverError {
     }
 }
 
+impl From<IndexerError> for ProverError {
+    fn from(e: IndexerError) -> ProverError {
+        ProverError::IndexerErr(e)
+    }
+}
+
+impl From<LowDegreeProverError> for ProverError {
+    fn from(e: LowDegreeProverError) -> ProverError {
+        ProverError::LowDegreeProverErr(e)
+    }
+}
+
 impl From<MerkleTreeError> for ProverError {
     fn from(e: MerkleTreeError) -> ProverError {
         ProverError::MerkleTreeErr(e)
     }
 }
 
+impl From<RandomCoinError> for ProverError {
+    fn from(e: RandomCoinError) -> ProverError {
+        ProverError::RandomCoinErr(e)
+    }
+}
+
+/// The error returned by the one-call [prove](crate::prover::prove)
+#[derive(Debug, Display, Error)]
+pub enum FractalError {
+    /// Proving failed: {0}
+    ProverErr(ProverError),
+}
+
+impl From<ProverError> for FractalError {
+    fn from(e: ProverError) -> FractalError {
+        FractalError::ProverErr(e)
+    }
+}
+
 /// Represents a generic error type
 #[derive(Debug, Display, Error)]
 pub enum LincheckError {
     /// If the Merkle Tree leads to an error
     MerkleTreeErr(MerkleTreeError),
+    /// If a proof component can't be assembled
+    ProofErr(ProofError),
+    /// If a sumcheck proof can't be generated
+    SumcheckErr(SumcheckProverError),
+    /// If the low degree proof for gamma can't be generated
+    LowDegreeErr(LowDegreeProverError),
+    /// If beta can't be drawn from the transcript
+    RandomCoinErr(RandomCoinError),
+}
+
+impl From<RandomCoinError> for LincheckError {
+    fn from(e: RandomCoinError) -> LincheckError {
+        LincheckError::RandomCoinErr(e)
+    }
+}
+
+impl From<LowDegreeProverError> for LincheckError {
+    fn from(e: LowDegreeProverError) -> LincheckError {
+        LincheckError::LowDegreeErr(e)
+    }
+}
+
+impl From<SumcheckProverError> for LincheckError {
+    fn from(e: SumcheckProverError) -> LincheckError {
+        LincheckError::SumcheckErr(e)
+    }
 }
 
 impl From<MerkleTreeError> for LincheckError {
@@ -46,6 +113,12 @@ impl From<MerkleTreeError> for LincheckError {
     }
 }
 
+impl From<ProofError> for LincheckError {
+    fn from(e: ProofError) -> LincheckError {
+        LincheckError::ProofErr(e)
+    }
+}
+
 // impl fmt::Display for LincheckError {
 //     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 //         match self {
@@ -79,6 +152,24 @@ impl fmt::Display for ProverError {
                     err,
                 )
             }
+            Self::InvalidFriOptions(reason) => {
+                write!(f, "Invalid FRI options: {}", reason)
+            }
+            Self::IndexerErr(err) => {
+                write!(f, "Encountered an error while indexing: {:?}", err)
+            }
+            Self::InvalidPublicInputs(reason) => {
+                write!(f, "Invalid public inputs: {}", reason)
+            }
+            Self::InvalidAssignment(reason) => {
+                write!(f, "Invalid assignment: {}", reason)
+            }
+            Self::LowDegreeProverErr(err) => {
+                write!(f, "Encountered an error in the low degree prover: {}", err)
+            }
+            Self::RandomCoinErr(err) => {
+                write!(f, "Failed to draw a challenge: {}", err)
+            }
         }
     }
 }
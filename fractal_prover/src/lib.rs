@@ -1,7 +1,11 @@
+use fractal_indexer::index::{try_build_index_domains_with_blowup, IndexDomains, IndexParams};
+use fractal_proofs::ProofOptions;
+use fractal_utils::DEFAULT_PAR_THRESHOLD;
 use winter_fri::FriOptions;
-use winter_math::StarkField;
+use winter_math::{get_power_series, StarkField};
 use log;
-mod errors;
+use errors::ProverError;
+pub mod errors;
 mod lincheck_prover;
 pub mod prover;
 mod rowcheck_prover;
@@ -23,4 +27,113 @@ pub struct FractalOptions<B: StarkField> {
     pub eta_k: B,
     pub fri_options: FriOptions,
     pub num_queries: usize,
+    // Evaluation domains at least this large are processed across threads by the provers'
+    // `concurrent` paths
+    pub par_threshold: usize,
+}
+
+impl<B: StarkField> FractalOptions<B> {
+    /// Builds the [FriOptions] for an evaluation domain of size `evaluation_domain_len`.
+    /// FRI stops folding once the layer is at most `max_remainder_size` elements, so a smaller
+    /// remainder gives a smaller proof for small instances and a larger one means fewer layers.
+    /// Returns an error, rather than panicking inside FRI, if the sizes don't fit together.
+    pub fn get_fri_options(
+        lde_blowup: usize,
+        folding_factor: usize,
+        max_remainder_size: usize,
+        evaluation_domain_len: usize,
+    ) -> Result<FriOptions, ProverError> {
+        if !lde_blowup.is_power_of_two() {
+            return Err(ProverError::InvalidFriOptions(format!(
+                "blowup factor must be a power of two, but was {}",
+                lde_blowup
+            )));
+        }
+        if folding_factor != 4 && folding_factor != 8 && folding_factor != 16 {
+            return Err(ProverError::InvalidFriOptions(format!(
+                "folding factor {} is not supported",
+                folding_factor
+            )));
+        }
+        if max_remainder_size < 2 * folding_factor {
+            return Err(ProverError::InvalidFriOptions(format!(
+                "max remainder size must be at least {}, but was {}",
+                2 * folding_factor,
+                max_remainder_size
+            )));
+        }
+        if max_remainder_size >= evaluation_domain_len {
+            return Err(ProverError::InvalidFriOptions(format!(
+                "max remainder size {} must be smaller than the evaluation domain size {}",
+                max_remainder_size, evaluation_domain_len
+            )));
+        }
+        Ok(FriOptions::new(lde_blowup, folding_factor, max_remainder_size))
+    }
+
+    /// Builds the options for an already indexed instance. The domains, the subgroup sizes
+    /// and the coset offsets all come from `index_domains`, so they agree with the keys the
+    /// indexer produced. FRI folds by 4 and stops at a remainder of at most 32 elements, or
+    /// half the evaluation domain for small instances. The queries are distinct positions of
+    /// the evaluation domain, so there must be at least one and fewer than its size.
+    pub fn from_index_domains(
+        index_domains: IndexDomains<B>,
+        degree_fs: usize,
+        lde_blowup: usize,
+        num_queries: usize,
+    ) -> Result<Self, ProverError> {
+        if num_queries == 0 || num_queries >= index_domains.l_field_len {
+            return Err(ProverError::InvalidFriOptions(format!(
+                "number of queries must be between 1 and {}, but was {}",
+                index_domains.l_field_len.saturating_sub(1),
+                num_queries
+            )));
+        }
+        let evaluation_domain =
+            get_power_series(index_domains.l_field_base, index_domains.l_field_len);
+        let max_remainder_size = std::cmp::min(32, evaluation_domain.len() / 2);
+        let fri_options =
+            Self::get_fri_options(lde_blowup, 4, max_remainder_size, evaluation_domain.len())?;
+        Ok(FractalOptions {
+            degree_fs,
+            size_subgroup_h: index_domains.h_field.len().next_power_of_two(),
+            size_subgroup_k: index_domains.k_field.len().next_power_of_two(),
+            summing_domain: index_domains.k_field,
+            evaluation_domain,
+            h_domain: index_domains.h_field,
+            eta: index_domains.eta,
+            eta_k: index_domains.eta_k,
+            fri_options,
+            num_queries,
+            par_threshold: DEFAULT_PAR_THRESHOLD,
+        })
+    }
+
+    /// Builds the options for an R1CS instance of the given size, computing every domain the
+    /// same way the indexer does. `num_non_zero` is the largest number of non-zero entries in
+    /// any of the three matrices. Returns an error, rather than panicking in the indexer, if
+    /// the sizes or the blowup factor don't give valid domains in `B`.
+    pub fn for_instance(
+        num_input_variables: usize,
+        num_constraints: usize,
+        num_non_zero: usize,
+        lde_blowup: usize,
+        num_queries: usize,
+    ) -> Result<Self, ProverError> {
+        if !lde_blowup.is_power_of_two() {
+            return Err(ProverError::InvalidFriOptions(format!(
+                "blowup factor must be a power of two, but was {}",
+                lde_blowup
+            )));
+        }
+        let index_params =
+            IndexParams::<B>::try_new(num_input_variables, num_constraints, num_non_zero)?;
+        let index_domains = try_build_index_domains_with_blowup::<B>(index_params, lde_blowup)?;
+        Self::from_index_domains(index_domains, num_input_variables, lde_blowup, num_queries)
+    }
+
+    /// The FRI options and number of queries proofs made with these options are verified against.
+    pub fn proof_options(&self) -> ProofOptions {
+        ProofOptions::new(self.fri_options.clone(), self.num_queries)
+    }
 }
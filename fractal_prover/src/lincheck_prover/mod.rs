@@ -1,9 +1,10 @@
 use std::{marker::PhantomData, usize};
 
-use fractal_indexer::{hash_values, snark_keys::*};
-use fractal_utils::polynomial_utils::*;
+use fractal_indexer::{hash_values, index::get_max_degree, snark_keys::*};
+use fractal_utils::{channel_utils::draw_lincheck_beta, polynomial_utils::*};
 
 use fractal_sumcheck::sumcheck_prover::*;
+use low_degree::low_degree_prover::LowDegreeProver;
 
 use fractal_proofs::{fft, polynom, LincheckProof, OracleQueries, TryInto};
 
@@ -15,6 +16,19 @@ use winter_utils::transpose_slice;
 use crate::{errors::LincheckError, FractalOptions, log::debug};
 
 const n: usize = 1;
+
+/// Computes the coefficients of u_H(X, alpha) = (X^|H| - alpha^|H|) / (X - alpha).
+/// This only depends on alpha and H, so it is shared by the linchecks for A, B and C.
+pub(crate) fn compute_u_alpha_coeffs<B: StarkField>(alpha: B, size_subgroup_h: usize) -> Vec<B> {
+    let alpha_to_h_size = alpha.exp(B::PositiveInteger::from(size_subgroup_h as u64));
+    debug!("alpha_to_h_size: {}", &alpha_to_h_size);
+    let mut u_numerator = vec![B::ZERO; size_subgroup_h];
+    u_numerator[0] = alpha_to_h_size.neg();
+    u_numerator.push(B::ONE);
+    let u_denominator = vec![alpha.neg(), B::ONE];
+    polynom::div(&u_numerator, &u_denominator)
+}
+
 // TODO: Will need to ask Irakliy whether a channel should be passed in here
 pub struct LincheckProver<
     'a,
@@ -26,6 +40,9 @@ pub struct LincheckProver<
     prover_matrix_index: &'a ProverMatrixIndex<H, B>,
     f_1_poly_coeffs: Vec<B>,
     f_2_poly_coeffs: Vec<B>,
+    u_alpha_coeffs: &'a Vec<B>,
+    // v_H(alpha), shared by the linchecks for A, B and C like u_alpha_coeffs
+    v_h_alpha: B,
     options: &'a FractalOptions<B>,
     _h: PhantomData<H>,
     _e: PhantomData<E>,
@@ -43,6 +60,8 @@ impl<
         prover_matrix_index: &'a ProverMatrixIndex<H, B>,
         f_1_poly_coeffs: Vec<B>,
         f_2_poly_coeffs: Vec<B>,
+        u_alpha_coeffs: &'a Vec<B>,
+        v_h_alpha: B,
         options: &'a FractalOptions<B>,
     ) -> Self {
         LincheckProver {
@@ -50,12 +69,25 @@ impl<
             prover_matrix_index,
             f_1_poly_coeffs,
             f_2_poly_coeffs,
+            u_alpha_coeffs,
+            v_h_alpha,
             options,
             _h: PhantomData,
             _e: PhantomData,
         }
     }
 
+    /// The degree bound the linchecks need from the index: the smallest power of two above the
+    /// degree of e, which is |H| - 1 in the product sumcheck and 2|K| - 3 in the matrix
+    /// sumcheck, where |K| is num_non_zero. This is what the indexer's get_max_degree computes.
+    pub fn get_max_degree_constraint(
+        num_input_variables: usize,
+        num_constraints: usize,
+        num_non_zero: usize,
+    ) -> usize {
+        get_max_degree(num_input_variables, num_constraints, num_non_zero)
+    }
+
     /// The polynomial t_alpha(X) = u_M(X, alpha). 
     /// We also know that u_M(X, alpha) = M_star(X, alpha).
     /// Further, M_star(X, Y) = 
@@ -74,9 +106,9 @@ impl<
             let k_term = self.prover_matrix_index.get_val_eval(summing_elt) * inv_denom_term;
             coefficient_values.push(k_term)
         }
-        // This is the v_h(alpha) term, which only needs to be computed once.
-        let v_h_alpha = compute_vanishing_poly(self.alpha, self.options.eta, self.options.size_subgroup_h);
-        //let v_h_alpha = vanishing_poly_for_mult_subgroup(self.alpha, self.options.size_subgroup_h);
+        let v_h = VanishingPolyEvaluator::new(self.options.eta, self.options.size_subgroup_h);
+        // This is the v_h(alpha) term, which is computed once for all three matrices.
+        let v_h_alpha = self.v_h_alpha;
         // Now we compute the terms sum_k (v_H(X)/ (X - row(k))) * (val(k)/ (alpha - col(k)))
         // over the eval domain.
         let mut t_evals = Vec::new();
@@ -92,7 +124,7 @@ impl<
                 sum_without_vs = sum_without_vs + prod_term;
             }
             // This is v_H(X).
-            let v_h_x = compute_vanishing_poly(x_val, self.options.eta, self.options.size_subgroup_h);
+            let v_h_x = v_h.eval(x_val);
             //let v_h_x = vanishing_poly_for_mult_subgroup(x_val, self.options.size_subgroup_h);
             // This is finally v_H(X) * v_H(alpha) * sum_K (1/ (X - row(k))) * (val(k)/ (alpha - col(k)))
             let sum_with_vs = (sum_without_vs * v_h_x) * v_h_alpha;
@@ -126,21 +158,8 @@ impl<
         // here are the steps to this:
         // 1. find out how polynomials are represented and get u_H(X, alpha) = (X^|H| - alpha)/(X - alpha)
         // 2. Polynom includes a mul and a sub function, use these to do the respective ops
-        //eta_to_h_size = eta.exp(B::PositiveInteger::from(self.options.size_subgroup_h));
-        let mut alpha_to_h_size = self.alpha.exp(B::PositiveInteger::from(self.options.size_subgroup_h as u64));
-        debug!("alpha_to_h_size: {}", &alpha_to_h_size);
-
-        let mut u_numerator = vec![B::ZERO; (self.options.size_subgroup_h).try_into().unwrap()];
-        u_numerator[0] = alpha_to_h_size.neg();
-        u_numerator.push(B::ONE);
-        let u_denominator = vec![self.alpha.neg(), B::ONE];
-        let u_alpha_evals: Vec<B> = self.options.evaluation_domain.iter().map(|e| polynom::eval(&u_numerator, *e) / polynom::eval(&u_denominator, *e)).collect();
-        let u_alpha_coeffs2 = polynom::interpolate(&self.options.evaluation_domain, &u_alpha_evals, true);
-        let mut u_alpha_coeffs = polynom::div(&u_numerator, &u_denominator);
-        let reconstituted = polynom::mul(&u_alpha_coeffs, &u_denominator);
-        
         let mut poly = polynom::sub(
-            &polynom::mul(&u_alpha_coeffs, &self.f_1_poly_coeffs),
+            &polynom::mul(self.u_alpha_coeffs, &self.f_1_poly_coeffs),
             &polynom::mul(t_alpha_coeffs, &self.f_2_poly_coeffs),
         );
         
@@ -156,12 +175,7 @@ impl<
         // 1. find out how polynomials are represented and get u_H(X, alpha) = (X^|H| - alpha)/(X - alpha)
         // 2. Polynom includes a mul and a sub function, use these to do the respective ops
         // botttom of page 29
-        let alpha_to_h_size = self.alpha.exp(B::PositiveInteger::from(self.options.size_subgroup_h as u64));
-        let mut u_numerator = vec![B::ZERO; (self.options.size_subgroup_h).try_into().unwrap()];
-        u_numerator[0] = alpha_to_h_size.neg();
-        u_numerator.push(B::ONE);
-        let u_denominator = vec![self.alpha.neg(), B::ONE];
-        let mut u_alpha = polynom::div(&u_numerator, &u_denominator);
+        let mut u_alpha = self.u_alpha_coeffs.clone();
         
         let mut prod = Vec::<B>::new();
         let eval_twiddles = fft::get_twiddles(self.options.evaluation_domain.len());
@@ -185,6 +199,16 @@ impl<
         prod
     }
 
+    /// The numerator v_H(alpha) * v_H(beta) * val(X) of the polynomial summed over K in the
+    /// matrix sumcheck.
+    pub(crate) fn generate_matrix_sumcheck_numerator(&self, beta: B) -> Vec<B> {
+        let v_h_beta = compute_vanishing_poly(beta, self.options.eta, self.options.size_subgroup_h);
+        polynom::mul_by_scalar(
+            &self.prover_matrix_index.val_poly.polynomial,
+            self.v_h_alpha * v_h_beta,
+        )
+    }
+
     pub fn generate_lincheck_proof(&self) -> Result<LincheckProof<B, E, H>, LincheckError> {
         let t_alpha_evals = self.generate_t_alpha_evals();
         let t_alpha = self.generate_t_alpha(t_alpha_evals.clone());
@@ -216,6 +240,14 @@ impl<
         let g_degree = self.options.h_domain.len() - 2;
         let e_degree = self.options.h_domain.len() - 1;
 
+        // Commit to t_alpha before beta is drawn, so gamma = t_alpha(beta) can be checked
+        // against it.
+        let t_alpha_evals_e: Vec<E> = t_alpha_evals.iter().map(|&eval| E::from(eval)).collect();
+        let t_alpha_transposed_evaluations = transpose_slice::<_, { n }>(&t_alpha_evals_e);
+        let hashed_evaluations = hash_values::<H, E, { n }>(&t_alpha_transposed_evaluations);
+        let t_alpha_tree = MerkleTree::<H>::new(hashed_evaluations)?;
+        let t_alpha_commitment = *t_alpha_tree.root();
+
         let mut product_sumcheck_prover = RationalSumcheckProver::<B, E, H>::new(
             poly_prod_coeffs.clone(),
             vec![B::ONE],
@@ -228,15 +260,23 @@ impl<
             self.options.fri_options.clone(),
             self.options.num_queries,
         );
-        let products_sumcheck_proof = product_sumcheck_prover.generate_proof();
-        let beta =
-            FieldElement::as_base_elements(&[product_sumcheck_prover.channel.draw_fri_alpha()])[0];
+        product_sumcheck_prover.set_par_threshold(self.options.par_threshold);
+        product_sumcheck_prover.channel.commit_fri_layer(t_alpha_commitment);
+        let products_sumcheck_proof = product_sumcheck_prover.generate_proof()?;
+        let beta = draw_lincheck_beta::<B, E, _>(&mut product_sumcheck_prover.channel)?;
         let gamma = polynom::eval(&t_alpha, beta);
-        let matrix_proof_numerator = polynom::mul_by_scalar(
-            &self.prover_matrix_index.val_poly.polynomial,
-            compute_vanishing_poly(self.alpha, self.options.eta, self.options.size_subgroup_h)
-                * compute_vanishing_poly(beta, self.options.eta, self.options.size_subgroup_h),
-        );
+        // gamma = t_alpha(beta) exactly when X - beta divides t_alpha - gamma. The quotient has
+        // degree |H| - 2, and its low degree proof draws the positions t_alpha is opened at.
+        product_sumcheck_prover.channel.commit_fri_layer(H::hash_elements(&[gamma]));
+        let gamma_quotient = polynom::syn_div(&t_alpha, 1, beta);
+        let gamma_proof = LowDegreeProver::<B, E, H>::from_polynomial(
+            &gamma_quotient,
+            &self.options.evaluation_domain,
+            self.options.h_domain.len() - 2,
+            self.options.fri_options.clone(),
+        )?
+        .generate_proof(&mut product_sumcheck_prover.channel);
+        let matrix_proof_numerator = self.generate_matrix_sumcheck_numerator(beta);
         let mut alpha_minus_row =
             polynom::mul_by_scalar(&self.prover_matrix_index.row_poly.polynomial, -B::ONE);
         alpha_minus_row[0] = alpha_minus_row[0] + self.alpha;
@@ -274,9 +314,12 @@ impl<
             self.options.fri_options.clone(),
             self.options.num_queries,
         );
-        let matrix_sumcheck_proof = matrix_sumcheck_prover.generate_proof();
+        matrix_sumcheck_prover.set_par_threshold(self.options.par_threshold);
+        let matrix_sumcheck_proof = matrix_sumcheck_prover.generate_proof()?;
 
-        let queried_positions = matrix_sumcheck_proof.queried_positions.clone();
+        // row, col and val are opened where e is, so the verifier can evaluate the summed
+        // polynomial at the points it checks the sumcheck identity at.
+        let queried_positions = matrix_sumcheck_proof.e_proof.queried_positions.clone();
 
         let row_queried_evaluations = queried_positions
             .iter()
@@ -293,7 +336,7 @@ impl<
             }
             row_proofs.push(row_proof?);
         }
-        let row_queried = OracleQueries::<B, E, H>::new(row_queried_evaluations, row_proofs);
+        let row_queried = OracleQueries::<B, E, H>::new(row_queried_evaluations, row_proofs)?;
 
         let col_queried_evaluations = queried_positions
             .iter()
@@ -310,7 +353,7 @@ impl<
             }
             col_proofs.push(col_proof?);
         }
-        let col_queried = OracleQueries::<B, E, H>::new(col_queried_evaluations, col_proofs);
+        let col_queried = OracleQueries::<B, E, H>::new(col_queried_evaluations, col_proofs)?;
 
         let val_queried_evaluations = queried_positions
             .iter()
@@ -327,17 +370,15 @@ impl<
             }
             val_proofs.push(val_proof?);
         }
-        let val_queried = OracleQueries::<B, E, H>::new(val_queried_evaluations, val_proofs);
+        let val_queried = OracleQueries::<B, E, H>::new(val_queried_evaluations, val_proofs)?;
 
-        let t_alpha_transposed_evaluations = transpose_slice::<_, { n }>(&t_alpha_evals.clone());
-        let hashed_evaluations = hash_values::<H, B, { n }>(&t_alpha_transposed_evaluations);
-        let t_alpha_tree = MerkleTree::<H>::new(hashed_evaluations)?;
-        let t_alpha_commitment = *t_alpha_tree.root();
-        let t_alpha_queried_evaluations = queried_positions
+        let t_alpha_queried_evaluations = gamma_proof
+            .queried_positions
             .iter()
-            .map(|&p| E::from(t_alpha_evals[p]))
+            .map(|&p| t_alpha_evals_e[p])
             .collect::<Vec<_>>();
-        let t_alpha_proofs_results = queried_positions
+        let t_alpha_proofs_results = gamma_proof
+            .queried_positions
             .iter()
             .map(|&p| t_alpha_tree.prove(p))
             .collect::<Vec<_>>();
@@ -349,7 +390,7 @@ impl<
             t_alpha_proofs.push(t_alpha_proof?);
         }
         let t_alpha_queried =
-            OracleQueries::<B, E, H>::new(t_alpha_queried_evaluations, t_alpha_proofs);
+            OracleQueries::<B, E, H>::new(t_alpha_queried_evaluations, t_alpha_proofs)?;
         Ok(LincheckProof::<B, E, H> {
             options: self.options.fri_options.clone(),
             num_evaluations: self.options.evaluation_domain.len(),
@@ -359,6 +400,7 @@ impl<
             t_alpha_queried,
             products_sumcheck_proof,
             gamma,
+            gamma_proof,
             row_queried,
             col_queried,
             val_queried,
@@ -1,15 +1,21 @@
 use std::marker::PhantomData;
 
-use fractal_indexer::snark_keys::*;
-use fractal_proofs::{fft, polynom, FractalProof, LincheckProof, TryInto};
-use models::r1cs::Matrix;
+use fractal_indexer::{
+    index::{try_build_index_domains_with_blowup, Index, IndexParams},
+    indexed_matrix::index_matrix,
+    snark_keys::*,
+};
+use fractal_utils::channel_utils::{hash_to_field, public_inputs_from_bytes};
+use fractal_utils::polynomial_utils::compute_vanishing_poly;
+use fractal_proofs::{fft, polynom, FieldTag, FractalProof, LincheckProof, ProofOptions, TryInto};
+use models::r1cs::{Matrix, R1CS};
 
 use winter_crypto::{ElementHasher, RandomCoin};
 use winter_math::{FieldElement, StarkField};
 
 use crate::{
-    errors::ProverError,
-    lincheck_prover::LincheckProver,
+    errors::{FractalError, ProverError},
+    lincheck_prover::{compute_u_alpha_coeffs, LincheckProver},
     rowcheck_prover::RowcheckProver,
     FractalOptions,
 };
@@ -23,6 +29,7 @@ pub struct FractalProver<
     options: FractalOptions<B>,
     witness: Vec<B>,
     variable_assignment: Vec<B>,
+    pub_inputs_bytes: Vec<u8>,
     public_coin: RandomCoin<B, H>,
     _e: PhantomData<E>,
 }
@@ -40,22 +47,48 @@ impl<
         variable_assignment: Vec<B>,
         pub_inputs_bytes: Vec<u8>,
     ) -> Self {
-        let coin_seed = pub_inputs_bytes;
+        let public_coin = RandomCoin::new(&pub_inputs_bytes);
         FractalProver {
             prover_key,
             options,
             witness,
             variable_assignment,
-            public_coin: RandomCoin::new(&coin_seed),
+            pub_inputs_bytes,
+            public_coin,
             _e: PhantomData,
         }
     }
 
+    /// The max_degree the index has to support for this prover: the larger of the rowcheck's
+    /// and the linchecks' degree constraints.
+    pub fn get_max_degree_constraint(
+        num_input_variables: usize,
+        num_constraints: usize,
+        num_non_zero: usize,
+    ) -> usize {
+        std::cmp::max(
+            RowcheckProver::<B, E, H>::get_max_degree_constraint(
+                num_input_variables,
+                num_constraints,
+            ),
+            LincheckProver::<B, E, H>::get_max_degree_constraint(
+                num_input_variables,
+                num_constraints,
+                num_non_zero,
+            ),
+        )
+    }
+
     pub fn generate_proof(&mut self) -> Result<FractalProof<B, E, H>, ProverError> {
+        let public_inputs = self.decode_public_inputs()?;
         // This is the less efficient version and assumes only dealing with the var assignment,
         // not z = (x, w)
-        let alpha = self.public_coin.draw().expect("failed to draw OOD point");
+        let alpha = hash_to_field(&mut self.public_coin)?;
         let inv_twiddles_h = fft::get_inv_twiddles(self.variable_assignment.len());
+        // u_H(X, alpha) is the same for all three linchecks, so only compute it once.
+        let u_alpha_coeffs = compute_u_alpha_coeffs(alpha, self.options.size_subgroup_h);
+        // So is v_H(alpha).
+        let v_h_alpha = compute_vanishing_poly(alpha, self.options.eta, self.options.size_subgroup_h);
 
         // 1. Generate lincheck proofs for the A,B,C matrices.
         let mut z_coeffs = &mut self.variable_assignment.clone();  // evals
@@ -69,7 +102,9 @@ impl<
             alpha,
             &self.prover_key.matrix_a_index,
             &z_coeffs.clone(),
-            &f_az_coeffs)?;
+            &f_az_coeffs,
+            &u_alpha_coeffs,
+            v_h_alpha)?;
 
         let f_bz_coeffs = self.compute_matrix_mul_poly_coeffs(
             &self.prover_key.matrix_b_index.matrix, 
@@ -80,7 +115,9 @@ impl<
             alpha,
             &self.prover_key.matrix_b_index,
             &z_coeffs.clone(),
-            &f_bz_coeffs)?;
+            &f_bz_coeffs,
+            &u_alpha_coeffs,
+            v_h_alpha)?;
 
         let f_cz_coeffs = self.compute_matrix_mul_poly_coeffs(
             &self.prover_key.matrix_c_index.matrix, 
@@ -91,7 +128,9 @@ impl<
             alpha,
             &self.prover_key.matrix_c_index,
             &z_coeffs.clone(),
-            &f_cz_coeffs)?;
+            &f_cz_coeffs,
+            &u_alpha_coeffs,
+            v_h_alpha)?;
         
         println!("Done with linchecks");
         
@@ -115,6 +154,9 @@ impl<
             f_az_coeffs,
             f_bz_coeffs,
             f_cz_coeffs,
+            z_coeffs.clone(),
+            public_inputs,
+            self.variable_assignment.len(),
             self.options.degree_fs,
             self.options.size_subgroup_h.try_into().unwrap(),
             self.options.evaluation_domain.clone(),
@@ -127,6 +169,8 @@ impl<
         println!("Done with rowcheck");
         // 3. Build and return an overall fractal proof.
         Ok(FractalProof {
+            field_tag: FieldTag::of::<B, E>(),
+            pub_inputs_hash: H::hash(&self.pub_inputs_bytes),
             rowcheck_proof,
             lincheck_a,
             lincheck_b,
@@ -134,6 +178,17 @@ impl<
         })
     }
 
+    // The transcript is seeded with pub_inputs_bytes. If the index declares public inputs, these
+    // are their values, which the rowcheck proves are the start of the assignment.
+    fn decode_public_inputs(&self) -> Result<Vec<B>, ProverError> {
+        let num_public_inputs = self.prover_key.params.num_public_inputs;
+        if num_public_inputs == 0 {
+            return Ok(Vec::new());
+        }
+        public_inputs_from_bytes(&self.pub_inputs_bytes, num_public_inputs)
+            .map_err(|err| ProverError::InvalidPublicInputs(err.to_string()))
+    }
+
     // Multiply a matrix times a vector of evaluations, then interpolate a poly and return its coeffs.
     fn compute_matrix_mul_poly_coeffs(
         &self,
@@ -153,16 +208,80 @@ impl<
         alpha: B,
         matrix_index: &ProverMatrixIndex<H, B>,
         z_coeffs: &Vec<B>,
-        prod_m_z_coeffs: &Vec<B>) -> Result<LincheckProof<B, E, H>, ProverError> {
+        prod_m_z_coeffs: &Vec<B>,
+        u_alpha_coeffs: &Vec<B>,
+        v_h_alpha: B) -> Result<LincheckProof<B, E, H>, ProverError> {
 
         let lincheck_prover = LincheckProver::<B, E, H>::new(
             alpha,
             &matrix_index,
             prod_m_z_coeffs.to_vec(),
             z_coeffs.to_vec(),
+            u_alpha_coeffs,
+            v_h_alpha,
             &self.options,
         );
         let lincheck_proof = lincheck_prover.generate_lincheck_proof()?;
         Ok(lincheck_proof)
     }
 }
+
+/// Indexes the R1CS instance given by `a`, `b` and `c` and proves that `assignment` satisfies
+/// it, picking default options: blowup 4, folding factor 4, and enough queries for roughly
+/// `security_bits` bits of (conjectured) FRI soundness. Returns the proof together with the
+/// verifier key and the [ProofOptions] needed to check it with
+/// `fractal_verifier::verifier::verify` and the same `pub_inputs_bytes`.
+pub fn prove<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher + ElementHasher<BaseField = B>,
+>(
+    a: Matrix<B>,
+    b: Matrix<B>,
+    c: Matrix<B>,
+    assignment: Vec<B>,
+    pub_inputs_bytes: Vec<u8>,
+    security_bits: usize,
+) -> Result<(FractalProof<B, E, H>, VerifierKey<H, B>, ProofOptions), FractalError> {
+    let lde_blowup = 4;
+    let mut r1cs = R1CS::new(a, b, c).map_err(ProverError::from)?;
+    if assignment.len() != r1cs.num_cols() {
+        return Err(ProverError::InvalidAssignment(format!(
+            "the assignment has {} values, but the R1CS has {} variables",
+            assignment.len(),
+            r1cs.num_cols()
+        ))
+        .into());
+    }
+    // The prover interpolates z and Mz over H, so the matrices have to be square with a power
+    // of two side. Zero rows are trivially satisfied and zero columns don't constrain z.
+    r1cs.pad_power_two();
+    r1cs.make_square();
+    let mut assignment = assignment;
+    assignment.resize(r1cs.num_cols(), B::ZERO);
+
+    let index_params = IndexParams::<B>::try_from_r1cs(&r1cs).map_err(ProverError::from)?;
+    let index_domains = try_build_index_domains_with_blowup::<B>(index_params.clone(), lde_blowup)
+        .map_err(ProverError::from)?;
+    let indexed_a = index_matrix::<B>(&r1cs.A, &index_domains);
+    let indexed_b = index_matrix::<B>(&r1cs.B, &index_domains);
+    let indexed_c = index_matrix::<B>(&r1cs.C, &index_domains);
+    let index = Index::try_new(index_params, indexed_a, indexed_b, indexed_c).map_err(ProverError::from)?;
+    let (prover_key, verifier_key) =
+        generate_prover_and_verifier_keys::<H, B, 1>(index).map_err(ProverError::from)?;
+
+    // Each FRI query contributes about log2(blowup) bits of security.
+    let bits_per_query = lde_blowup.trailing_zeros() as usize;
+    let num_queries = (security_bits + bits_per_query - 1) / bits_per_query;
+    let options = FractalOptions::<B>::from_index_domains(
+        index_domains,
+        r1cs.num_cols(),
+        lde_blowup,
+        num_queries,
+    )?;
+    let proof_options = options.proof_options();
+    let mut prover =
+        FractalProver::<B, E, H>::new(prover_key, options, vec![], assignment, pub_inputs_bytes);
+    let proof = prover.generate_proof()?;
+    Ok((proof, verifier_key, proof_options))
+}
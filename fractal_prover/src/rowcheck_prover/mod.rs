@@ -3,9 +3,10 @@ use std::{convert::TryInto, marker::PhantomData};
 use fractal_indexer::hash_values;
 use fractal_utils::polynomial_utils::*;
 use fractal_proofs::{RowcheckProof, polynom};
+use low_degree::low_degree_prover::LowDegreeProver;
 
 use winter_crypto::{ElementHasher, Hasher, MerkleTree};
-use winter_fri::{DefaultProverChannel, FriOptions};
+use winter_fri::{DefaultProverChannel, FriOptions, ProverChannel};
 use winter_math::{FieldElement, StarkField};
 use winter_utils::transpose_slice;
 
@@ -15,6 +16,10 @@ pub struct RowcheckProver<B: StarkField, E: FieldElement<BaseField = B>, H: Hash
     f_az_coeffs: Vec<B>,
     f_bz_coeffs: Vec<B>,
     f_cz_coeffs: Vec<B>,
+    z_coeffs: Vec<B>,
+    // The public inputs, i.e. the first values of z, and the size of the domain z is interpolated over
+    public_inputs: Vec<B>,
+    z_domain_size: usize,
     degree_fs: usize,
     size_subgroup_h: usize,
     evaluation_domain: Vec<B>,
@@ -33,6 +38,9 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
         f_az_coeffs: Vec<B>,
         f_bz_coeffs: Vec<B>,
         f_cz_coeffs: Vec<B>,
+        z_coeffs: Vec<B>,
+        public_inputs: Vec<B>,
+        z_domain_size: usize,
         degree_fs: usize,
         size_subgroup_h: usize,
         evaluation_domain: Vec<B>,
@@ -45,6 +53,9 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
             f_az_coeffs,
             f_bz_coeffs,
             f_cz_coeffs,
+            z_coeffs,
+            public_inputs,
+            z_domain_size,
             degree_fs,
             size_subgroup_h,
             evaluation_domain,
@@ -57,23 +68,41 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
         }
     }
 
+    /// The degree bound the rowcheck needs from the index: the smallest power of two above the
+    /// degree of s, which is at most |H| - 2, where H is sized by the larger of
+    /// num_input_variables and num_constraints.
+    pub fn get_max_degree_constraint(num_input_variables: usize, num_constraints: usize) -> usize {
+        let s_degree = std::cmp::max(num_input_variables, num_constraints).saturating_sub(2);
+        (s_degree + 1).next_power_of_two()
+    }
+
     pub fn generate_proof(&self) -> Result<RowcheckProof<B, E, H>, ProverError> {
-        let mut denom_poly = vec![B::ZERO; self.size_subgroup_h-1];
-        denom_poly.push(B::ONE);
+        // s = (f_Az * f_Bz - f_Cz) / v_H, where v_H(X) = X^|H| - eta^|H| vanishes on eta * H
         let h_size_32: u32 = self.size_subgroup_h.try_into().unwrap();
         let eta_pow = B::PositiveInteger::from(h_size_32);
-        denom_poly[0] = self.eta.exp(eta_pow).neg();
-        let s_coeffs = polynom::div(
-            &polynom::sub(&polynom::mul(&self.f_az_coeffs, &self.f_bz_coeffs), &self.f_cz_coeffs),
-            &denom_poly
-        );   
+        let mut numerator =
+            polynom::sub(&polynom::mul(&self.f_az_coeffs, &self.f_bz_coeffs), &self.f_cz_coeffs);
+        // syn_div needs more coefficients than the degree of v_H
+        numerator.resize(std::cmp::max(numerator.len(), self.size_subgroup_h + 1), B::ZERO);
+        let s_coeffs = polynom::syn_div(&numerator, self.size_subgroup_h, self.eta.exp(eta_pow));
+        let s_max_degree = self.size_subgroup_h - 2;
+
+        // Commit to f_Az, f_Bz and f_Cz, so the verifier can check s against them, and to f_z,
+        // so it can check z starts with the public inputs.
+        let [f_az_all, f_bz_all, f_cz_all, f_z_all] =
+            [&self.f_az_coeffs, &self.f_bz_coeffs, &self.f_cz_coeffs, &self.z_coeffs]
+                .map(|coeffs| polynom::eval_many(coeffs, &self.evaluation_domain));
+        let f_evals: Vec<[E; 4]> = (0..self.evaluation_domain.len())
+            .map(|i| [f_az_all[i], f_bz_all[i], f_cz_all[i], f_z_all[i]].map(E::from))
+            .collect();
+        let f_tree = MerkleTree::<H>::new(hash_values::<H, E, 4>(&f_evals))?;
         let old_s_evals_b: Vec<B> = polynom::eval_many(s_coeffs.clone().as_slice(), self.evaluation_domain.clone().as_slice());// Vec::new();
         let old_s_evals: Vec<E> = old_s_evals_b.into_iter().map(|x: B| {E::from(x)}).collect();
         let transposed_evaluations = transpose_slice(&old_s_evals);
         let hashed_evaluations = hash_values::<H, E, 1>(&transposed_evaluations);
         let s_tree = MerkleTree::<H>::new(hashed_evaluations)?;
         
-        let s_comp_coeffs = get_complementary_poly::<B>(polynom::degree_of(&s_coeffs), self.max_degree - 1);
+        let s_comp_coeffs = get_complementary_poly::<B>(s_max_degree, self.max_degree - 1);
         let new_s = polynom::mul(&s_coeffs, &s_comp_coeffs);
 
         let s_evals_b: Vec<B> = polynom::eval_many(new_s.clone().as_slice(), self.evaluation_domain.clone().as_slice());// Vec::new();
@@ -83,16 +112,34 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
         let mut fri_prover =
             winter_fri::FriProver::<B, E, DefaultProverChannel<B, E, H>, H>::new(self.fri_options.clone());
 
-        let query_positions = channel.draw_query_positions();
+        // Both trees are fixed before the queries are drawn, see verify_rowcheck_proof.
+        let f_eval_root = *f_tree.root();
+        let s_eval_root = *s_tree.root();
+        channel.commit_fri_layer(f_eval_root);
+        channel.commit_fri_layer(s_eval_root);
+        // The low degree proof for w commits to w and then draws the queries for the whole rowcheck.
+        let w_coeffs = self.compute_w_coeffs();
+        let w_proof = LowDegreeProver::<B, E, H>::from_polynomial(
+            &w_coeffs,
+            &self.evaluation_domain,
+            w_max_degree(self.z_domain_size, self.public_inputs.len()),
+            self.fri_options.clone(),
+        )?
+        .generate_proof(&mut channel);
+        let commitment_idx = channel.layer_commitments().len();
+        let query_positions = w_proof.queried_positions.clone();
         let queried_positions = query_positions.clone();
 
-        let s_eval_root = *s_tree.root();
         let s_original_evals = query_positions
             .iter()
             .map(|&p| old_s_evals[p])
             .collect::<Vec<_>>();
         
         let s_original_proof = s_tree.prove_batch(&queried_positions)?;
+        let f_eval_proof = f_tree.prove_batch(&queried_positions)?;
+        let [f_az_evals, f_bz_evals, f_cz_evals, f_z_evals] = [0, 1, 2, 3].map(|column| {
+            query_positions.iter().map(|&p| f_evals[p][column]).collect::<Vec<_>>()
+        });
 
         // Build proofs for the polynomial g
         fri_prover.build_layers(&mut channel, s_evals.clone());
@@ -101,18 +148,43 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
             .iter()
             .map(|&p| s_evals[p])
             .collect::<Vec<_>>();
-        let s_commitments = channel.layer_commitments().to_vec();
+        // use only the FRI commitments for s, not the tree roots or the commitments of w_proof
+        let s_commitments = channel.layer_commitments()[commitment_idx..].to_vec();
         Ok(RowcheckProof {
             options: self.fri_options.clone(),
             num_evaluations: self.evaluation_domain.len(),
             queried_positions,
+            f_eval_root,
+            f_az_evals,
+            f_bz_evals,
+            f_cz_evals,
+            f_z_evals,
+            f_eval_proof,
+            w_proof,
             s_eval_root,
             s_original_evals,
             s_original_proof,
             s_proof,
             s_queried_evals,
             s_commitments,
-            s_max_degree: self.size_subgroup_h - 2,
+            s_max_degree,
         })
     }
+
+    // w = (z - x_hat) / v_X. If z doesn't start with the public inputs the division leaves a
+    // remainder, which is dropped, so z = x_hat + v_X * w fails at almost every point.
+    fn compute_w_coeffs(&self) -> Vec<B> {
+        let (x_hat, v_x) = public_input_polys(&self.public_inputs, self.eta, self.z_domain_size);
+        let numerator = polynom::sub(&self.z_coeffs, &x_hat);
+        if polynom::degree_of(&numerator) < polynom::degree_of(&v_x) {
+            return vec![B::ZERO];
+        }
+        polynom::div(&numerator, &v_x)
+    }
+}
+
+// The degree bound for w = (z - x_hat) / v_X: z has degree below z_domain_size and v_X has
+// degree num_public_inputs.
+fn w_max_degree(z_domain_size: usize, num_public_inputs: usize) -> usize {
+    z_domain_size.saturating_sub(num_public_inputs + 1)
 }
@@ -1 +1,314 @@
+use crate::errors::{FractalError, ProverError};
+use crate::lincheck_prover::compute_u_alpha_coeffs;
+use crate::lincheck_prover::LincheckProver;
+use crate::prover::{prove, FractalProver};
+use crate::rowcheck_prover::RowcheckProver;
+use crate::FractalOptions;
 
+use fractal_indexer::index::{
+    build_index_domains_with_blowup, eval_domain_size, get_max_degree, is_valid_coset_offset,
+    Index, IndexParams,
+};
+use fractal_indexer::errors::IndexerError;
+use fractal_indexer::{
+    indexed_matrix::index_matrix,
+    snark_keys::{generate_prover_and_verifier_keys, ProverKey},
+};
+
+use fractal_proofs::{fft, polynom, Serializable};
+use fractal_utils::polynomial_utils::compute_vanishing_poly;
+use fractal_utils::DEFAULT_PAR_THRESHOLD;
+use models::r1cs::{Matrix, R1CS};
+use winter_crypto::hashers::Rp64_256;
+use winter_math::fields::f64::BaseElement;
+use winter_math::{get_power_series, FieldElement, StarkField};
+
+#[test]
+fn test_shared_u_alpha_matches_interpolation() {
+    // The linchecks used to each interpolate u_H(X, alpha) over the evaluation domain;
+    // the shared coefficients must describe the same polynomial.
+    let size_subgroup_h = 16;
+    let alpha = BaseElement::new(1337);
+    let eval_domain_base = BaseElement::get_root_of_unity(6);
+    let evaluation_domain = get_power_series(eval_domain_base, 64);
+
+    let alpha_to_h_size = alpha.exp(size_subgroup_h as u64);
+    let u_alpha_evals: Vec<BaseElement> = evaluation_domain
+        .iter()
+        .map(|&x| (x.exp(size_subgroup_h as u64) - alpha_to_h_size) / (x - alpha))
+        .collect();
+    let mut interpolated = polynom::interpolate(&evaluation_domain, &u_alpha_evals, true);
+    interpolated.truncate(polynom::degree_of(&interpolated) + 1);
+
+    let u_alpha_coeffs = compute_u_alpha_coeffs(alpha, size_subgroup_h);
+    assert_eq!(u_alpha_coeffs.len(), size_subgroup_h);
+    assert_eq!(interpolated, u_alpha_coeffs);
+}
+
+#[test]
+fn test_max_degree_constraint() {
+    type B = BaseElement;
+    // the first two are too small for |H| - 2 and 2|K| - 3 to be non-negative
+    for &(num_inputs, num_constraints, num_non_zero) in
+        &[(1, 1, 1), (2, 1, 1), (4, 4, 2), (8, 4, 16), (16, 32, 8), (64, 64, 64), (128, 16, 256)]
+    {
+        let rowcheck =
+            RowcheckProver::<B, B, Rp64_256>::get_max_degree_constraint(num_inputs, num_constraints);
+        let lincheck = LincheckProver::<B, B, Rp64_256>::get_max_degree_constraint(
+            num_inputs,
+            num_constraints,
+            num_non_zero,
+        );
+        let max_degree = FractalProver::<B, B, Rp64_256>::get_max_degree_constraint(
+            num_inputs,
+            num_constraints,
+            num_non_zero,
+        );
+        // both are the smallest power of two above the degree they bound
+        let s_degree = std::cmp::max(num_inputs, num_constraints).saturating_sub(2);
+        assert!(rowcheck.is_power_of_two() && rowcheck > s_degree && rowcheck / 2 <= s_degree);
+        assert!(rowcheck <= lincheck);
+        assert_eq!(max_degree, std::cmp::max(rowcheck, lincheck));
+        // and it is what the index is built with
+        assert_eq!(max_degree, get_max_degree(num_inputs, num_constraints, num_non_zero));
+    }
+}
+
+#[test]
+fn test_options_for_instance() {
+    let lde_blowup = 4;
+    let options = FractalOptions::<BaseElement>::for_instance(5, 6, 9, lde_blowup, 16).unwrap();
+    assert_eq!(options.h_domain.len(), 8);
+    assert_eq!(options.summing_domain.len(), 16);
+    assert_eq!(options.size_subgroup_h, options.h_domain.len());
+    assert_eq!(options.size_subgroup_k, options.summing_domain.len());
+    let max_degree = get_max_degree(8, 8, 16);
+    assert_eq!(
+        options.evaluation_domain.len(),
+        eval_domain_size(max_degree, lde_blowup)
+    );
+    assert!(is_valid_coset_offset(options.eta, options.h_domain.len()));
+    assert!(is_valid_coset_offset(options.eta_k, options.summing_domain.len()));
+    assert_ne!(options.eta, options.eta_k);
+
+    assert!(FractalOptions::<BaseElement>::for_instance(5, 6, 9, 3, 16).is_err());
+    // sizes the indexer can't build domains for are errors, not panics
+    for (num_input_variables, num_constraints, blowup) in
+        [(0, 6, lde_blowup), (5, 1 << 40, lde_blowup), (5, 6, 1 << 31)]
+    {
+        assert!(matches!(
+            FractalOptions::<BaseElement>::for_instance(num_input_variables, num_constraints, 9, blowup, 16),
+            Err(ProverError::IndexerErr(IndexerError::InvalidIndexParams(_)))
+        ));
+    }
+    assert_eq!(options.par_threshold, DEFAULT_PAR_THRESHOLD);
+}
+
+#[test]
+fn test_par_threshold_does_not_change_proof() {
+    // The threshold only picks how e is evaluated in the lincheck's sumchecks, so proving with
+    // every domain parallelized and with none must give the same proof.
+    let (prover_key, options, z) = get_chain_setup(8);
+
+    let alpha = BaseElement::new(1337);
+    let u_alpha_coeffs = compute_u_alpha_coeffs(alpha, options.size_subgroup_h);
+    let v_h_alpha = compute_vanishing_poly(alpha, options.eta, options.size_subgroup_h);
+    let inv_twiddles = fft::get_inv_twiddles(z.len());
+    let interpolate = |mut evals: Vec<BaseElement>| {
+        fft::interpolate_poly_with_offset(&mut evals, &inv_twiddles, options.eta);
+        evals
+    };
+    let z_coeffs = interpolate(z.clone());
+    let mz_coeffs = interpolate(prover_key.matrix_a_index.matrix.dot(&z));
+    let proofs = [0, usize::MAX].map(|par_threshold| {
+        let options = FractalOptions { par_threshold, ..options.clone() };
+        LincheckProver::<BaseElement, BaseElement, Rp64_256>::new(
+            alpha,
+            &prover_key.matrix_a_index,
+            mz_coeffs.clone(),
+            z_coeffs.clone(),
+            &u_alpha_coeffs,
+            v_h_alpha,
+            &options,
+        )
+        .generate_lincheck_proof()
+        .unwrap()
+        .to_bytes()
+    });
+    assert_eq!(proofs[0], proofs[1]);
+}
+
+#[test]
+fn test_proof_size_breakdown() {
+    let (a, b, c, z) = get_chain_circuit(8);
+    let (proof, _, _) = prove::<BaseElement, BaseElement, Rp64_256>(a, b, c, z, vec![0u8], 32).unwrap();
+
+    let report = proof.size_breakdown();
+    println!("{:?}", report);
+    assert_eq!(report.pub_inputs_hash, 32);
+    assert!(report.rowcheck > 0 && report.lincheck_a > 0);
+    assert_eq!(report.total(), proof.to_bytes().len());
+}
+
+#[test]
+fn test_prove_rejects_bad_input() {
+    let (a, b, c, z) = get_chain_circuit(8);
+    assert!(matches!(
+        prove::<BaseElement, BaseElement, Rp64_256>(
+            a.clone(),
+            b.clone(),
+            c.clone(),
+            z[..4].to_vec(),
+            vec![0u8],
+            32
+        ),
+        Err(FractalError::ProverErr(ProverError::InvalidAssignment(_)))
+    ));
+    for security_bits in [0, 1000] {
+        assert!(matches!(
+            prove::<BaseElement, BaseElement, Rp64_256>(
+                a.clone(),
+                b.clone(),
+                c.clone(),
+                z.clone(),
+                vec![0u8],
+                security_bits
+            ),
+            Err(FractalError::ProverErr(ProverError::InvalidFriOptions(_)))
+        ));
+    }
+
+    // instances too small to index, or with too small an evaluation domain for the queries
+    for size in [1, 2] {
+        let (a, b, c, z) = get_identity_circuit(size);
+        assert!(prove::<BaseElement, BaseElement, Rp64_256>(a, b, c, z, vec![0u8], 32).is_err());
+    }
+    // sizes that aren't powers of two are padded
+    let (a, b, c, z) = get_identity_circuit(3);
+    assert!(prove::<BaseElement, BaseElement, Rp64_256>(a, b, c, z, vec![0u8], 32).is_ok());
+}
+
+#[test]
+fn test_shared_v_h_alpha_matches_per_matrix() {
+    // v_H(alpha) used to be computed by each lincheck on its own. Recompute it for every matrix
+    // as the product of (alpha - h) over H, and check the t_alpha evaluations and the matrix
+    // sumcheck numerator built from the shared value against it.
+    let (prover_key, options, z) = get_chain_setup(8);
+    let alpha = BaseElement::new(1337);
+    let beta = BaseElement::new(4242);
+    let u_alpha_coeffs = compute_u_alpha_coeffs(alpha, options.size_subgroup_h);
+    // computed once, the way FractalProver does
+    let shared_v_h_alpha = compute_vanishing_poly(alpha, options.eta, options.size_subgroup_h);
+    let v_h = |x: BaseElement| {
+        options.h_domain.iter().fold(BaseElement::ONE, |acc, &h| acc * (x - h))
+    };
+
+    let inv_twiddles = fft::get_inv_twiddles(z.len());
+    let interpolate = |mut evals: Vec<BaseElement>| {
+        fft::interpolate_poly_with_offset(&mut evals, &inv_twiddles, options.eta);
+        evals
+    };
+    let z_coeffs = interpolate(z.clone());
+    for matrix_index in [&prover_key.matrix_a_index, &prover_key.matrix_b_index, &prover_key.matrix_c_index] {
+        let lincheck_prover = LincheckProver::<BaseElement, BaseElement, Rp64_256>::new(
+            alpha,
+            matrix_index,
+            interpolate(matrix_index.matrix.dot(&z)),
+            z_coeffs.clone(),
+            &u_alpha_coeffs,
+            shared_v_h_alpha,
+            &options,
+        );
+        let v_h_alpha = v_h(alpha);
+
+        // t_alpha(x) = v_H(x) * v_H(alpha) * sum_k val(k) / ((x - row(k)) * (alpha - col(k)))
+        let expected_t_alpha_evals: Vec<BaseElement> = options
+            .evaluation_domain
+            .iter()
+            .map(|&x| {
+                let sum = options.summing_domain.iter().fold(BaseElement::ZERO, |acc, &k| {
+                    acc + matrix_index.get_val_eval(k)
+                        / ((x - matrix_index.get_row_eval(k)) * (alpha - matrix_index.get_col_eval(k)))
+                });
+                v_h(x) * v_h_alpha * sum
+            })
+            .collect();
+        assert_eq!(lincheck_prover.generate_t_alpha_evals(), expected_t_alpha_evals);
+
+        let expected_numerator =
+            polynom::mul_by_scalar(&matrix_index.val_poly.polynomial, v_h_alpha * v_h(beta));
+        assert_eq!(lincheck_prover.generate_matrix_sumcheck_numerator(beta), expected_numerator);
+    }
+}
+
+// The prover key and options for get_chain_circuit(size) with blowup 4 and 16 queries, along
+// with its assignment.
+fn get_chain_setup(
+    size: usize,
+) -> (ProverKey<Rp64_256, BaseElement>, FractalOptions<BaseElement>, Vec<BaseElement>) {
+    let lde_blowup = 4;
+    let (a, b, c, z) = get_chain_circuit(size);
+    let r1cs = R1CS::new(a, b, c).unwrap();
+    let index_params = IndexParams::<BaseElement>::from_r1cs(&r1cs);
+    let index_domains = build_index_domains_with_blowup::<BaseElement>(index_params.clone(), lde_blowup);
+    let index = Index::new(
+        index_params,
+        index_matrix::<BaseElement>(&r1cs.A, &index_domains),
+        index_matrix::<BaseElement>(&r1cs.B, &index_domains),
+        index_matrix::<BaseElement>(&r1cs.C, &index_domains),
+    );
+    let (prover_key, _) = generate_prover_and_verifier_keys::<Rp64_256, BaseElement, 1>(index).unwrap();
+    let options =
+        FractalOptions::<BaseElement>::from_index_domains(index_domains, r1cs.num_cols(), lde_blowup, 16)
+            .unwrap();
+    (prover_key, options, z)
+}
+
+// z = (1, 2, 3, 6, 18, ...) with z_i * z_{i+1} = z_{i+2} in every row but the last two
+fn get_chain_circuit(
+    size: usize,
+) -> (Matrix<BaseElement>, Matrix<BaseElement>, Matrix<BaseElement>, Vec<BaseElement>) {
+    let mut z = vec![BaseElement::ONE, BaseElement::new(2), BaseElement::new(3)];
+    for i in 1..size - 2 {
+        z.push(z[i] * z[i + 1]);
+    }
+    let unit_row = |col: usize| {
+        let mut row = vec![BaseElement::ZERO; size];
+        row[col] = BaseElement::ONE;
+        row
+    };
+    let mut a_rows = vec![vec![BaseElement::ZERO; size]; size];
+    let mut b_rows = a_rows.clone();
+    let mut c_rows = a_rows.clone();
+    for i in 1..size - 2 {
+        a_rows[i - 1] = unit_row(i);
+        b_rows[i - 1] = unit_row(i + 1);
+        c_rows[i - 1] = unit_row(i + 2);
+    }
+    (
+        Matrix::new("a", a_rows).unwrap(),
+        Matrix::new("b", b_rows).unwrap(),
+        Matrix::new("c", c_rows).unwrap(),
+        z,
+    )
+}
+
+// z = (1, ..., 1) with z_i * z_i = z_i in every row. f_Az is constant over H, which used to
+// trip up the sumcheck prover.
+fn get_identity_circuit(
+    size: usize,
+) -> (Matrix<BaseElement>, Matrix<BaseElement>, Matrix<BaseElement>, Vec<BaseElement>) {
+    let rows: Vec<Vec<BaseElement>> = (0..size)
+        .map(|i| {
+            let mut row = vec![BaseElement::ZERO; size];
+            row[i] = BaseElement::ONE;
+            row
+        })
+        .collect();
+    (
+        Matrix::new("a", rows.clone()).unwrap(),
+        Matrix::new("b", rows.clone()).unwrap(),
+        Matrix::new("c", rows).unwrap(),
+        vec![BaseElement::ONE; size],
+    )
+}
@@ -0,0 +1,68 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fractal_sumcheck::sumcheck_prover::RationalSumcheckProver;
+use std::time::Duration;
+use winter_crypto::hashers::Rp64_256;
+use winter_fri::FriOptions;
+use winter_math::{fields::f64::BaseElement, get_power_series, get_power_series_with_offset, StarkField};
+
+const SUMMING_DOMAIN_SIZES: [usize; 2] = [1 << 12, 1 << 16];
+const BLOWUP: usize = 4;
+
+// Computes e from the evaluations of g over L, for a sumcheck shaped like the lincheck's: p has
+// degree about 2|K|, and q is either 1 (product sumcheck) or of degree about 2|K| (matrix
+// sumcheck). Run with `--features concurrent` to compare against the parallel path.
+fn e_coeffs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sumcheck_e_coeffs");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    for &summing_domain_size in SUMMING_DOMAIN_SIZES.iter() {
+        let eval_domain_size = BLOWUP * 2 * summing_domain_size;
+        let eta = BaseElement::GENERATOR;
+        let summing_domain = get_power_series_with_offset(
+            BaseElement::get_root_of_unity(summing_domain_size.trailing_zeros()),
+            eta,
+            summing_domain_size,
+        );
+        let evaluation_domain = get_power_series(
+            BaseElement::get_root_of_unity(eval_domain_size.trailing_zeros()),
+            eval_domain_size,
+        );
+        let numerator_coeffs: Vec<BaseElement> = (0..2 * summing_domain_size as u64)
+            .map(BaseElement::new)
+            .collect();
+        let denominator_coeffs: Vec<BaseElement> = (1..2 * summing_domain_size as u64)
+            .map(BaseElement::new)
+            .collect();
+        let g_evals: Vec<BaseElement> = (0..eval_domain_size as u64)
+            .map(|i| BaseElement::new(i * i + 7))
+            .collect();
+
+        for (name, denominator) in [
+            ("unit_denominator", vec![BaseElement::new(1)]),
+            ("denominator", denominator_coeffs),
+        ] {
+            let prover = RationalSumcheckProver::<BaseElement, BaseElement, Rp64_256>::new(
+                numerator_coeffs.clone(),
+                denominator,
+                BaseElement::new(0),
+                summing_domain.clone(),
+                eta,
+                evaluation_domain.clone(),
+                summing_domain_size - 2,
+                eval_domain_size / 2 - 1,
+                FriOptions::new(BLOWUP, 4, 32),
+                16,
+            );
+            group.bench_with_input(
+                BenchmarkId::new(name, summing_domain_size),
+                &g_evals,
+                |bench, g_evals| bench.iter(|| prover.compute_e_coeffs(g_evals)),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(sumcheck_group, e_coeffs);
+criterion_main!(sumcheck_group);
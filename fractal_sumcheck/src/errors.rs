@@ -8,7 +8,23 @@
 //! Errors for various data structure operations.
 //use winter_fri::VerifierError;
 use winter_utils::DeserializationError;
-use low_degree::errors::LowDegreeVerifierError;
+use low_degree::errors::{LowDegreeProverError, LowDegreeVerifierError};
+
+/// The two polynomials a sumcheck proves low degree for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SumcheckPolynomial {
+    G,
+    E,
+}
+
+impl std::fmt::Display for SumcheckPolynomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            SumcheckPolynomial::G => write!(f, "g"),
+            SumcheckPolynomial::E => write!(f, "e"),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum SumcheckVerifierError {
@@ -16,6 +32,16 @@ pub enum SumcheckVerifierError {
     FriVerifierErr(LowDegreeVerifierError),
     /// Error propagation
     DeserializationErr(DeserializationError),
+    /// The proof's options or sizes disagree with its sub-proofs or with the expected degrees
+    OptionsMismatchErr(String),
+    /// The low degree proof of g or e failed
+    PolynomialErr(SumcheckPolynomial, LowDegreeVerifierError),
+    /// g, p or q doesn't have one value per position the e proof queries
+    QueriesMismatchErr,
+    /// The g opening at this queried position doesn't match the committed g
+    GOpeningErr(usize),
+    /// q * (X * g + sigma / |K|) - p != v_K * e at this queried position
+    IdentityErr(usize),
 }
 
 impl From<LowDegreeVerifierError> for SumcheckVerifierError {
@@ -39,6 +65,43 @@ impl std::fmt::Display for SumcheckVerifierError {
             SumcheckVerifierError::DeserializationErr(err) => {
                 writeln!(f, "Winterfell Utils Deserialization Error: {}", err)
             }
+            SumcheckVerifierError::OptionsMismatchErr(err) => {
+                writeln!(f, "Sumcheck Options Mismatch Error: {}", err)
+            }
+            SumcheckVerifierError::PolynomialErr(polynomial, err) => {
+                writeln!(f, "Low degree proof of {} failed: {}", polynomial, err)
+            }
+            SumcheckVerifierError::QueriesMismatchErr => {
+                writeln!(f, "Sumcheck queries don't match the e proof's queried positions")
+            }
+            SumcheckVerifierError::GOpeningErr(position) => {
+                writeln!(f, "Opening of g at position {} is invalid", position)
+            }
+            SumcheckVerifierError::IdentityErr(position) => {
+                writeln!(f, "Sumcheck identity doesn't hold at position {}", position)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SumcheckProverError {
+    /// Error propagation
+    LowDegreeProverErr(LowDegreeProverError),
+}
+
+impl From<LowDegreeProverError> for SumcheckProverError {
+    fn from(error: LowDegreeProverError) -> Self {
+        Self::LowDegreeProverErr(error)
+    }
+}
+
+impl std::fmt::Display for SumcheckProverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            SumcheckProverError::LowDegreeProverErr(err) => {
+                writeln!(f, "Low Degree Prover Error: {}", err)
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,30 @@
+#[cfg(test)]
+use std::cell::Cell;
 use std::{convert::TryInto, marker::PhantomData};
 
-use fractal_utils::polynomial_utils::*;
+use fractal_utils::{polynomial_utils::*, should_parallelize, DEFAULT_PAR_THRESHOLD};
 use low_degree::low_degree_prover::LowDegreeProver;
 use winter_crypto::ElementHasher;
-use winter_fri::{DefaultProverChannel, FriOptions};
-use winter_math::{fft, FieldElement, StarkField};
+use winter_fri::{DefaultProverChannel, FriOptions, ProverChannel};
+use winter_math::{batch_inversion, fft, FieldElement, StarkField};
+use winter_utils::iter;
+use crate::errors::SumcheckProverError;
 use crate::log::debug;
 
+#[cfg(feature = "concurrent")]
+use winter_utils::iterators::*;
+
 use fractal_proofs::{OracleQueries, SumcheckProof, polynom};
 #[cfg(test)]
 mod tests;
 
+/// Evaluation domains with at least this many points have g evaluated and e interpolated on
+/// them with FFTs rather than with `polynom::eval_many` and `polynom::interpolate`. The FFTs
+/// also compute their twiddles, which dominates below this size: in a release build on f64,
+/// evaluating a degree n/4 polynomial and interpolating one over n points takes about 1.8µs
+/// directly vs 2.6µs with FFTs at n = 8, and about 20µs vs 4µs at n = 32.
+pub const DEFAULT_FFT_THRESHOLD: usize = 16;
+
 pub struct RationalSumcheckProver<
     B: StarkField,
     E: FieldElement<BaseField = B>,
@@ -32,6 +46,16 @@ pub struct RationalSumcheckProver<
     g_degree: usize,
     e_degree: usize,
     fri_options: FriOptions,
+    // Evaluation domains at least this large use FFTs, see [DEFAULT_FFT_THRESHOLD]
+    fft_threshold: usize,
+    // The FFTs evaluate over <omega> = [1, omega, omega^2, ...]; any other evaluation domain
+    // is evaluated and interpolated directly
+    fft_friendly_domain: bool,
+    // Evaluation domains at least this large are split across threads, see [should_parallelize]
+    par_threshold: usize,
+    // Number of times compute_e_evals took the parallel path, so tests can check which one ran
+    #[cfg(test)]
+    parallel_e_evals: Cell<usize>,
     pub channel: DefaultProverChannel<B, E, H>,
     _h: PhantomData<H>,
 }
@@ -53,6 +77,7 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
     ) -> Self {
         let summing_domain_twiddles = fft::get_twiddles(summing_domain.len());
         let channel = DefaultProverChannel::new(evaluation_domain.len(), num_queries);
+        let fft_friendly_domain = is_fft_domain(&evaluation_domain);
         RationalSumcheckProver {
             numerator_coeffs,
             denominator_coeffs,
@@ -64,73 +89,42 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
             g_degree,
             e_degree,
             fri_options,
+            fft_threshold: DEFAULT_FFT_THRESHOLD,
+            fft_friendly_domain,
+            par_threshold: DEFAULT_PAR_THRESHOLD,
+            #[cfg(test)]
+            parallel_e_evals: Cell::new(0),
             channel,
             _h: PhantomData,
         }
     }
 
-    pub fn generate_proof(&mut self) -> SumcheckProof<B, E, H> {
+    pub fn generate_proof(&mut self) -> Result<SumcheckProof<B, E, H>, SumcheckProverError> {
         // compute the polynomial g such that Sigma(g, sigma) = summing_poly
         // compute the polynomial e such that e = (Sigma(g, sigma) - summing_poly)/v_H over the summing domain H.
         debug!("Starting a sumcheck proof");
+        // Bind the claimed sum to the transcript before g and e are committed, so
+        // that sigma cannot be swapped out once the low degree proofs exist.
+        self.channel.commit_fri_layer(H::hash_elements(&[self.sigma]));
         let _sigma_inv = self.sigma.inv();
         
 
-        //might be faster to eval_many
-        let f_hat_evals: Vec<B> = self.summing_domain.iter().map(|x| polynom::eval(&self.numerator_coeffs, *x) / polynom::eval(&self.denominator_coeffs, *x)).collect();
-
-        let summing_domain_e: Vec<E> = self.summing_domain.iter().map(|f| E::from(*f) ).collect();
-        let f_hat_coeffs = polynom::interpolate(&self.summing_domain, &f_hat_evals, true);
-        let x_coeffs = vec![B::ZERO, B::ONE];
-        let sub_factor = self.sigma / B::from(self.summing_domain.len() as u64);
-        let f_hat_minus_sub_factor = polynom::sub(&f_hat_coeffs, &vec![sub_factor]);
-        assert_eq!(f_hat_minus_sub_factor[0], B::ZERO);
-        let g_hat_coeffs = polynom::div(&f_hat_minus_sub_factor, &x_coeffs);
-        
-
-
-        let eval_domain_e: Vec<E> = self.evaluation_domain.iter().map(|f| E::from(*f) ).collect();
-
-        debug!("self.evaluation_domain.len(): {:?}", &self.evaluation_domain.len());
-        
-
-
-        ////g_hat test
-        let dividing_factor_for_sigma: u64 = self.summing_domain.len().try_into().unwrap();
-        let subtracting_factor = self.sigma * B::from(dividing_factor_for_sigma).inv();
-
-        let g_eval_domain_evals = polynom::eval_many(&g_hat_coeffs, &self.evaluation_domain);
-
-        let p_eval_domain_evals = polynom::eval_many(&self.numerator_coeffs, &self.evaluation_domain);
-        let q_eval_domain_evals = polynom::eval_many(&self.denominator_coeffs, &self.evaluation_domain);
-
-        let mut e_eval_domain_evals: Vec<B> = Vec::new();
-        for i in 0..self.evaluation_domain.len() {
-            let e_val = self.compute_e_poly_on_val(
-                self.evaluation_domain[i],
-                g_eval_domain_evals[i],
-                p_eval_domain_evals[i],
-                q_eval_domain_evals[i],
-                self.eta,
-            );
-            e_eval_domain_evals.push(e_val);
-        }
-
-        let e_hat_coeffs = polynom::interpolate(&self.evaluation_domain, &e_eval_domain_evals, true);
-        debug!("degree of e: {}", polynom::degree_of(&e_hat_coeffs));
+        let (g_hat_coeffs, e_hat_coeffs) = self.compute_g_and_e_coeffs();
         
         let query_positions = self.channel.draw_query_positions();
         let queried_positions = query_positions.clone();
 
         // Build proofs for the polynomial g
-        let g_prover = LowDegreeProver::<B, E, H>::from_polynomial(&g_hat_coeffs, &self.evaluation_domain, self.g_degree, self.fri_options.clone());
+        let g_prover = LowDegreeProver::<B, E, H>::from_polynomial(&g_hat_coeffs, &self.evaluation_domain, self.g_degree, self.fri_options.clone())?;
         let g_proof = g_prover.generate_proof(&mut self.channel);
 
         // Build proofs for the polynomial e
-        let e_prover = LowDegreeProver::<B, E, H>::from_polynomial(&e_hat_coeffs, &self.evaluation_domain, self.e_degree, self.fri_options.clone());
+        let e_prover = LowDegreeProver::<B, E, H>::from_polynomial(&e_hat_coeffs, &self.evaluation_domain, self.e_degree, self.fri_options.clone())?;
         let e_proof = e_prover.generate_proof(&mut self.channel);
+        // Open g where e is queried, so the verifier can check the sumcheck identity there.
+        let g_queried = g_prover.open(&e_proof.queried_positions);
 
-        SumcheckProof {
+        Ok(SumcheckProof {
             options: self.fri_options.clone(),
             num_evaluations: self.evaluation_domain.len(),
             queried_positions,
@@ -138,6 +132,136 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
             g_max_degree: self.g_degree,
             e_proof: e_proof,
             e_max_degree: self.e_degree,
+            g_queried,
+        })
+    }
+
+    /// Computes the coefficients of g and e. g is read off f over the summing domain, then g is
+    /// evaluated over the evaluation domain to get e there, and e is interpolated back.
+    pub fn compute_g_and_e_coeffs(&self) -> (Vec<B>, Vec<B>) {
+        let f_hat_evals = self.compute_f_hat_evals();
+        let f_hat_coeffs = polynom::interpolate(&self.summing_domain, &f_hat_evals, true);
+        let sub_factor = self.sigma / B::from(self.summing_domain.len() as u64);
+        let f_hat_minus_sub_factor = polynom::sub(&f_hat_coeffs, &vec![sub_factor]);
+        assert_eq!(f_hat_minus_sub_factor[0], B::ZERO);
+        // The constant term is zero, so dividing by X drops it. polynom::div would panic if f
+        // were constant over the summing domain.
+        let g_hat_coeffs = f_hat_minus_sub_factor[1..].to_vec();
+
+        debug!("self.evaluation_domain.len(): {:?}", &self.evaluation_domain.len());
+        let twiddles = self.eval_domain_twiddles();
+        let g_eval_domain_evals = self.evaluate_on_eval_domain(&g_hat_coeffs, twiddles.as_deref());
+        let e_hat_coeffs = self.compute_e_coeffs(&g_eval_domain_evals);
+        debug!("degree of e: {}", polynom::degree_of(&e_hat_coeffs));
+        (g_hat_coeffs, e_hat_coeffs)
+    }
+
+    /// Computes the coefficients of e from the evaluations of g over the evaluation domain.
+    pub fn compute_e_coeffs(&self, g_eval_domain_evals: &Vec<B>) -> Vec<B> {
+        let e_eval_domain_evals = self.compute_e_evals(g_eval_domain_evals);
+        if self.use_fft() {
+            let mut coeffs = e_eval_domain_evals;
+            fft::interpolate_poly(&mut coeffs, &fft::get_inv_twiddles(self.evaluation_domain.len()));
+            polynom::remove_leading_zeros(&coeffs)
+        } else {
+            polynom::interpolate(&self.evaluation_domain, &e_eval_domain_evals, true)
+        }
+    }
+
+    /// Whether g and e are evaluated and interpolated over the evaluation domain with FFTs: the
+    /// domain has to be large enough, see [DEFAULT_FFT_THRESHOLD], and be the subgroup the FFTs
+    /// evaluate over, in its natural order.
+    fn use_fft(&self) -> bool {
+        self.fft_friendly_domain && self.evaluation_domain.len() >= self.fft_threshold
+    }
+
+    /// The twiddles for FFTs over the evaluation domain, if they are used, see [Self::use_fft].
+    fn eval_domain_twiddles(&self) -> Option<Vec<B>> {
+        if self.use_fft() {
+            Some(fft::get_twiddles(self.evaluation_domain.len()))
+        } else {
+            None
+        }
+    }
+
+    /// Evaluates a polynomial over the evaluation domain, with an FFT if `twiddles` are given
+    /// and the polynomial fits in the domain.
+    fn evaluate_on_eval_domain(&self, coeffs: &[B], twiddles: Option<&[B]>) -> Vec<B> {
+        match twiddles {
+            Some(twiddles) if coeffs.len() <= self.evaluation_domain.len() => {
+                let mut evals = coeffs.to_vec();
+                evals.resize(self.evaluation_domain.len(), B::ZERO);
+                fft::evaluate_poly(&mut evals, twiddles);
+                evals
+            }
+            _ => polynom::eval_many(coeffs, &self.evaluation_domain),
+        }
+    }
+
+    /// Sets the size of the evaluation domain from which g and e are evaluated and
+    /// interpolated there with FFTs, see [DEFAULT_FFT_THRESHOLD].
+    pub fn set_fft_threshold(&mut self, fft_threshold: usize) {
+        self.fft_threshold = fft_threshold;
+    }
+
+    /// Sets the size of the evaluation domain from which e is evaluated across threads,
+    /// see [fractal_utils::should_parallelize].
+    pub fn set_par_threshold(&mut self, par_threshold: usize) {
+        self.par_threshold = par_threshold;
+    }
+
+    /// True if the denominator q(x) is the constant 1, as in the product sumcheck of the lincheck.
+    pub fn has_unit_denominator(&self) -> bool {
+        self.denominator_coeffs == [B::ONE]
+    }
+
+    /// Evaluates f = p/q over the summing domain. The denominator evaluations are inverted in
+    /// one batch, and not at all if q is 1.
+    pub fn compute_f_hat_evals(&self) -> Vec<B> {
+        let numerator_evals = polynom::eval_many(&self.numerator_coeffs, &self.summing_domain);
+        if self.has_unit_denominator() {
+            return numerator_evals;
+        }
+        let denominator_evals = polynom::eval_many(&self.denominator_coeffs, &self.summing_domain);
+        numerator_evals
+            .iter()
+            .zip(batch_inversion(&denominator_evals))
+            .map(|(&num, den_inv)| num * den_inv)
+            .collect()
+    }
+
+    /// Evaluates e over the evaluation domain, given the evaluations of g there.
+    /// e = ((x * g + sigma / |K|) * q - p) / v_H is computed in evaluation form: p and q are
+    /// evaluated over the domain with FFTs, which split their butterflies across threads with
+    /// the `concurrent` feature, and the products, the subtraction and the division by v_H are
+    /// done point by point, split across threads once the domain reaches the prover's
+    /// parallelization threshold.
+    pub fn compute_e_evals(&self, g_eval_domain_evals: &Vec<B>) -> Vec<B> {
+        let summing_domain_len = self.summing_domain.len();
+        let sigma_over_size = self.sigma * B::from(summing_domain_len as u64).inv();
+        let twiddles = self.eval_domain_twiddles();
+        let numerator_evals = self.evaluate_on_eval_domain(&self.numerator_coeffs, twiddles.as_deref());
+        let denominator_evals = if self.has_unit_denominator() {
+            None
+        } else {
+            Some(self.evaluate_on_eval_domain(&self.denominator_coeffs, twiddles.as_deref()))
+        };
+        let vanishing_poly = VanishingPolyEvaluator::new(self.eta, summing_domain_len);
+        let e_at = |(i, &x_val): (usize, &B)| {
+            let sigma_function = x_val * g_eval_domain_evals[i] + sigma_over_size;
+            let sigma_times_q = match &denominator_evals {
+                Some(denominator_evals) => sigma_function * denominator_evals[i],
+                None => sigma_function,
+            };
+            let sigma_minus_f = sigma_times_q - numerator_evals[i];
+            sigma_minus_f * vanishing_poly.eval(x_val).inv()
+        };
+        if should_parallelize(self.evaluation_domain.len(), self.par_threshold) {
+            #[cfg(test)]
+            self.parallel_e_evals.set(self.parallel_e_evals.get() + 1);
+            iter!(self.evaluation_domain).enumerate().map(e_at).collect()
+        } else {
+            self.evaluation_domain.iter().enumerate().map(e_at).collect()
         }
     }
 
@@ -172,3 +296,22 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
         sigma_minus_f * vanishing_on_x.inv()
     }
 }
+
+/// True if `domain` is [1, omega, omega^2, ...] for omega a root of unity of order
+/// `domain.len()`, the domain `fft::evaluate_poly` and `fft::interpolate_poly` work over.
+/// Shifted cosets and permuted subgroups are not.
+fn is_fft_domain<B: StarkField>(domain: &[B]) -> bool {
+    let len = domain.len();
+    if !len.is_power_of_two() || len.trailing_zeros() > B::TWO_ADICITY {
+        return false;
+    }
+    let omega = B::get_root_of_unity(len.trailing_zeros());
+    let mut expected = B::ONE;
+    for &x in domain {
+        if x != expected {
+            return false;
+        }
+        expected *= omega;
+    }
+    true
+}
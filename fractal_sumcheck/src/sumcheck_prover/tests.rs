@@ -1,4 +1,188 @@
+use super::{RationalSumcheckProver, DEFAULT_FFT_THRESHOLD};
+
+use fractal_proofs::polynom;
+use winter_crypto::hashers::Rp64_256;
+use winter_fri::FriOptions;
+use winter_math::fields::f64::BaseElement;
+use winter_math::{get_power_series, get_power_series_with_offset, FieldElement, StarkField};
+
 #[test]
 fn check_sigma_correct() {
     // this test should check if compute sigma's value equals the value of the summing poly.
 }
+
+#[test]
+fn test_e_evals_match_pointwise() {
+    // The threshold picks the path: usize::MAX keeps e serial, 0 always takes the parallel
+    // path, which with the concurrent feature on is split across threads.
+    for (eval_domain_size, par_threshold) in [(64usize, usize::MAX), (64, 0), (256, 128)] {
+        let summing_domain_size = 16;
+        let eta = BaseElement::GENERATOR;
+        let summing_domain = get_power_series_with_offset(
+            BaseElement::get_root_of_unity(4),
+            eta,
+            summing_domain_size,
+        );
+        let evaluation_domain = get_power_series(
+            BaseElement::get_root_of_unity(eval_domain_size.trailing_zeros()),
+            eval_domain_size,
+        );
+        let numerator_coeffs: Vec<BaseElement> = (1..10u64).map(BaseElement::new).collect();
+        let denominator_coeffs = vec![BaseElement::new(3), BaseElement::ONE];
+        let g_coeffs: Vec<BaseElement> = (5..15u64).map(BaseElement::new).collect();
+
+        let mut prover = RationalSumcheckProver::<BaseElement, BaseElement, Rp64_256>::new(
+            numerator_coeffs.clone(),
+            denominator_coeffs.clone(),
+            BaseElement::new(42),
+            summing_domain,
+            eta,
+            evaluation_domain.clone(),
+            summing_domain_size - 2,
+            eval_domain_size / 4 - 1,
+            FriOptions::new(4, 4, 32),
+            16,
+        );
+        prover.set_par_threshold(par_threshold);
+        let g_evals = polynom::eval_many(&g_coeffs, &evaluation_domain);
+        let e_evals = prover.compute_e_evals(&g_evals);
+        let expected_parallel = usize::from(eval_domain_size >= par_threshold);
+        assert_eq!(prover.parallel_e_evals.get(), expected_parallel);
+
+        assert_eq!(e_evals.len(), eval_domain_size);
+        for (i, &x_val) in evaluation_domain.iter().enumerate() {
+            let expected = prover.compute_e_poly_on_val(
+                x_val,
+                g_evals[i],
+                polynom::eval(&numerator_coeffs, x_val),
+                polynom::eval(&denominator_coeffs, x_val),
+                eta,
+            );
+            assert_eq!(e_evals[i], expected);
+        }
+    }
+}
+
+#[test]
+fn test_unit_denominator_matches_division() {
+    let summing_domain_size = 16;
+    let eval_domain_size = 64;
+    let eta = BaseElement::GENERATOR;
+    let summing_domain = get_power_series_with_offset(
+        BaseElement::get_root_of_unity(4),
+        eta,
+        summing_domain_size,
+    );
+    let evaluation_domain = get_power_series(BaseElement::get_root_of_unity(6), eval_domain_size);
+    let numerator_coeffs: Vec<BaseElement> = (1..10u64).map(BaseElement::new).collect();
+    let g_coeffs: Vec<BaseElement> = (5..15u64).map(BaseElement::new).collect();
+
+    // q = 1, once as the unit denominator and once padded so it takes the general path
+    let make_prover = |denominator_coeffs: Vec<BaseElement>| {
+        RationalSumcheckProver::<BaseElement, BaseElement, Rp64_256>::new(
+            numerator_coeffs.clone(),
+            denominator_coeffs,
+            BaseElement::new(42),
+            summing_domain.clone(),
+            eta,
+            evaluation_domain.clone(),
+            summing_domain_size - 2,
+            summing_domain_size - 1,
+            FriOptions::new(4, 4, 32),
+            16,
+        )
+    };
+    let unit_prover = make_prover(vec![BaseElement::ONE]);
+    let padded_prover = make_prover(vec![BaseElement::ONE, BaseElement::ZERO]);
+    assert!(unit_prover.has_unit_denominator());
+    assert!(!padded_prover.has_unit_denominator());
+
+    let expected_f_hat = polynom::eval_many(&numerator_coeffs, &summing_domain);
+    assert_eq!(unit_prover.compute_f_hat_evals(), expected_f_hat);
+    assert_eq!(padded_prover.compute_f_hat_evals(), expected_f_hat);
+
+    let g_evals = polynom::eval_many(&g_coeffs, &evaluation_domain);
+    assert_eq!(
+        unit_prover.compute_e_evals(&g_evals),
+        padded_prover.compute_e_evals(&g_evals)
+    );
+}
+
+#[test]
+fn test_fft_and_direct_paths_match() {
+    let summing_domain_size = 16;
+    let eta = BaseElement::GENERATOR;
+    let summing_domain = get_power_series_with_offset(
+        BaseElement::get_root_of_unity(4),
+        eta,
+        summing_domain_size,
+    );
+    // p is chosen so that it sums to sigma over the summing domain
+    let mut numerator_coeffs: Vec<BaseElement> = (1..10u64).map(BaseElement::new).collect();
+    numerator_coeffs[0] = BaseElement::ZERO;
+    let sigma = BaseElement::ZERO;
+    for eval_domain_size in [DEFAULT_FFT_THRESHOLD, 64] {
+        let evaluation_domain = get_power_series(
+            BaseElement::get_root_of_unity(eval_domain_size.trailing_zeros()),
+            eval_domain_size,
+        );
+        let mut prover = RationalSumcheckProver::<BaseElement, BaseElement, Rp64_256>::new(
+            numerator_coeffs.clone(),
+            vec![BaseElement::ONE],
+            sigma,
+            summing_domain.clone(),
+            eta,
+            evaluation_domain,
+            summing_domain_size - 2,
+            summing_domain_size - 1,
+            FriOptions::new(4, 4, 32),
+            16,
+        );
+        prover.set_fft_threshold(0);
+        let (fft_g, fft_e) = prover.compute_g_and_e_coeffs();
+        prover.set_fft_threshold(usize::MAX);
+        let (direct_g, direct_e) = prover.compute_g_and_e_coeffs();
+        assert_eq!(fft_g, direct_g);
+        assert_eq!(fft_e, direct_e);
+    }
+}
+
+#[test]
+fn test_fft_path_needs_unshifted_natural_order_domain() {
+    let summing_domain_size = 16;
+    let eval_domain_size: usize = 64;
+    let eta = BaseElement::GENERATOR;
+    let summing_domain = get_power_series_with_offset(
+        BaseElement::get_root_of_unity(4),
+        eta,
+        summing_domain_size,
+    );
+    let mut numerator_coeffs: Vec<BaseElement> = (1..10u64).map(BaseElement::new).collect();
+    numerator_coeffs[0] = BaseElement::ZERO;
+    let omega = BaseElement::get_root_of_unity(eval_domain_size.trailing_zeros());
+    let new_prover = |evaluation_domain: Vec<BaseElement>| {
+        let mut prover = RationalSumcheckProver::<BaseElement, BaseElement, Rp64_256>::new(
+            numerator_coeffs.clone(),
+            vec![BaseElement::ONE],
+            BaseElement::ZERO,
+            summing_domain.clone(),
+            eta,
+            evaluation_domain,
+            summing_domain_size - 2,
+            summing_domain_size - 1,
+            FriOptions::new(4, 4, 32),
+            16,
+        );
+        prover.set_fft_threshold(0);
+        prover
+    };
+    let expected = new_prover(get_power_series(omega, eval_domain_size)).compute_g_and_e_coeffs();
+
+    // a coset of the subgroup, and the subgroup out of order, are evaluated directly
+    let coset = get_power_series_with_offset(omega, eta.square(), eval_domain_size);
+    let mut permuted = get_power_series(omega, eval_domain_size);
+    permuted.swap(1, 2);
+    for evaluation_domain in [coset, permuted] {
+        assert_eq!(new_prover(evaluation_domain).compute_g_and_e_coeffs(), expected);
+    }
+}
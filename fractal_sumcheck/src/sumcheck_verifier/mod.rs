@@ -1,9 +1,10 @@
-use crate::errors::SumcheckVerifierError;
+use crate::errors::{SumcheckPolynomial, SumcheckVerifierError};
 
-use fractal_proofs::{FieldElement, SumcheckProof};
+use fractal_proofs::{FieldElement, ProofOptions, SumcheckProof};
+use fractal_utils::polynomial_utils::VanishingPolyEvaluator;
 
-use low_degree::low_degree_verifier::verify_low_degree_proof;
-use winter_crypto::{ElementHasher, RandomCoin};
+use low_degree::low_degree_verifier::{get_queried_domain_elements, verify_low_degree_proof_ref};
+use winter_crypto::{ElementHasher, MerkleTree, RandomCoin};
 use winter_fri::{DefaultVerifierChannel, FriVerifier};
 use winter_math::StarkField;
 
@@ -20,11 +21,162 @@ pub fn verify_sumcheck_proof<
     proof: SumcheckProof<B, E, H>,
     g_max_degree: usize,
     e_max_degree: usize,
+    sigma: B,
+    options: &ProofOptions,
+) -> Result<(), SumcheckVerifierError> {
+    verify_sumcheck_proof_ref(&proof, g_max_degree, e_max_degree, sigma, options)
+}
+
+/// Same as [verify_sumcheck_proof], but borrows the proof.
+pub fn verify_sumcheck_proof_ref<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: &SumcheckProof<B, E, H>,
+    g_max_degree: usize,
+    e_max_degree: usize,
+    sigma: B,
+    options: &ProofOptions,
 ) -> Result<(), SumcheckVerifierError> {
 
     let mut public_coin = RandomCoin::new(&[]);
-    verify_low_degree_proof(proof.g_proof, g_max_degree, &mut public_coin)?;
-    verify_low_degree_proof(proof.e_proof, e_max_degree, &mut public_coin)?;
-    // FIXME: This proof verification should also check that e and g are correct wrt the Az, Bz and Cz.
+    verify_sumcheck_proof_with_coin_ref(proof, g_max_degree, e_max_degree, sigma, options, &mut public_coin)
+}
+
+/// Same as [verify_sumcheck_proof], but replays the transcript on `public_coin`, which must be
+/// fresh. Afterwards the coin is in the same state as the prover's channel once the proof is
+/// generated, so callers can draw the challenges the prover drew from it next.
+pub fn verify_sumcheck_proof_with_coin<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: SumcheckProof<B, E, H>,
+    g_max_degree: usize,
+    e_max_degree: usize,
+    sigma: B,
+    options: &ProofOptions,
+    public_coin: &mut RandomCoin<B, H>,
+) -> Result<(), SumcheckVerifierError> {
+    verify_sumcheck_proof_with_coin_ref(&proof, g_max_degree, e_max_degree, sigma, options, public_coin)
+}
+
+/// Same as [verify_sumcheck_proof_with_coin], but borrows the proof.
+pub fn verify_sumcheck_proof_with_coin_ref<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: &SumcheckProof<B, E, H>,
+    g_max_degree: usize,
+    e_max_degree: usize,
+    sigma: B,
+    options: &ProofOptions,
+    public_coin: &mut RandomCoin<B, H>,
+) -> Result<(), SumcheckVerifierError> {
+    check_proof_options(proof, g_max_degree, e_max_degree, options)?;
+    // The prover commits to sigma before anything else, see RationalSumcheckProver::generate_proof
+    public_coin.reseed(H::hash_elements(&[sigma]));
+    verify_low_degree_proof_ref(&proof.g_proof, g_max_degree, options, public_coin)
+        .map_err(|err| SumcheckVerifierError::PolynomialErr(SumcheckPolynomial::G, err))?;
+    verify_low_degree_proof_ref(&proof.e_proof, e_max_degree, options, public_coin)
+        .map_err(|err| SumcheckVerifierError::PolynomialErr(SumcheckPolynomial::E, err))?;
+    // The identity relating g and e to the summed polynomial is checked by the caller, which
+    // knows that polynomial, see verify_sumcheck_queries.
+    Ok(())
+}
+
+/// Checks q(x) * (x * g(x) + sigma / |K|) - p(x) = v_K(x) * e(x) at every position the e proof
+/// queries, where p / q is the summed polynomial and K = eta * K_0 the summing domain.
+/// `numerator_evals` and `denominator_evals` are p and q at those positions, in order. Call it
+/// after the proof itself verified, so e's queried evaluations are the committed ones.
+///
+/// v_K is evaluated at every queried point, so eta^|K| is computed once up front.
+pub fn verify_sumcheck_queries<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: &SumcheckProof<B, E, H>,
+    summing_domain_size: usize,
+    eta: B,
+    sigma: B,
+    numerator_evals: &[E],
+    denominator_evals: &[E],
+) -> Result<(), SumcheckVerifierError> {
+    let positions = &proof.e_proof.queried_positions;
+    let g_queried = &proof.g_queried;
+    let e_evals = &proof.e_proof.unpadded_queried_evaluations;
+    if g_queried.queried_evals.len() != positions.len()
+        || g_queried.queried_proofs.len() != positions.len()
+        || e_evals.len() != positions.len()
+        || numerator_evals.len() != positions.len()
+        || denominator_evals.len() != positions.len()
+    {
+        return Err(SumcheckVerifierError::QueriesMismatchErr);
+    }
+    // a path holds the leaf, its sibling and one node per level above them
+    let path_len = proof.num_evaluations.trailing_zeros() as usize + 1;
+    for (i, &position) in positions.iter().enumerate() {
+        let path = &g_queried.queried_proofs[i];
+        if path.len() != path_len
+            || path[0] != H::hash_elements(&[g_queried.queried_evals[i]])
+            || MerkleTree::<H>::verify(proof.g_proof.tree_root, position, path).is_err()
+        {
+            return Err(SumcheckVerifierError::GOpeningErr(position));
+        }
+    }
+
+    let v_k = VanishingPolyEvaluator::new(E::from(eta), summing_domain_size);
+    let sigma_over_size = E::from(sigma / B::from(summing_domain_size as u64));
+    let xs = get_queried_domain_elements::<B, E>(proof.num_evaluations, B::ONE, positions);
+    for (i, x) in xs.into_iter().enumerate() {
+        let sigma_function = x * g_queried.queried_evals[i] + sigma_over_size;
+        if denominator_evals[i] * sigma_function - numerator_evals[i] != v_k.eval(x) * e_evals[i] {
+            return Err(SumcheckVerifierError::IdentityErr(positions[i]));
+        }
+    }
+    Ok(())
+}
+
+/// The FRI options and domain size of a sumcheck proof are the ones g and e were proven with.
+/// Checks that they are the verifier's, that the sub-proofs agree with them, and that the proof
+/// was made for the degrees the verifier expects.
+fn check_proof_options<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: &SumcheckProof<B, E, H>,
+    g_max_degree: usize,
+    e_max_degree: usize,
+    options: &ProofOptions,
+) -> Result<(), SumcheckVerifierError> {
+    if proof.options != options.fri_options {
+        return Err(SumcheckVerifierError::OptionsMismatchErr(
+            "FRI options of the sumcheck proof differ from the verifier's".to_string(),
+        ));
+    }
+    for (name, sub_proof) in [("g", &proof.g_proof), ("e", &proof.e_proof)] {
+        if sub_proof.options != proof.options {
+            return Err(SumcheckVerifierError::OptionsMismatchErr(format!(
+                "FRI options of the {} proof differ from the sumcheck options",
+                name
+            )));
+        }
+        if sub_proof.num_evaluations != proof.num_evaluations {
+            return Err(SumcheckVerifierError::OptionsMismatchErr(format!(
+                "{} was evaluated over {} points, but the sumcheck domain has {}",
+                name, sub_proof.num_evaluations, proof.num_evaluations
+            )));
+        }
+    }
+    if proof.g_max_degree != g_max_degree || proof.e_max_degree != e_max_degree {
+        return Err(SumcheckVerifierError::OptionsMismatchErr(format!(
+            "proof is for degrees ({}, {}), expected ({}, {})",
+            proof.g_max_degree, proof.e_max_degree, g_max_degree, e_max_degree
+        )));
+    }
     Ok(())
 }
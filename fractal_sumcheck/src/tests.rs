@@ -1 +1,166 @@
+use crate::errors::SumcheckVerifierError;
+use crate::sumcheck_prover::RationalSumcheckProver;
+use crate::sumcheck_verifier::{verify_sumcheck_proof, verify_sumcheck_proof_ref, verify_sumcheck_queries};
 
+use crate::errors::SumcheckPolynomial;
+use fractal_proofs::{polynom, ByteReader, ProofOptions, Serializable, SliceReader, SumcheckProof};
+use low_degree::errors::LowDegreeVerifierError;
+use winter_crypto::hashers::Rp64_256;
+use winter_fri::FriOptions;
+use winter_math::fields::f64::BaseElement;
+use winter_math::{get_power_series, get_power_series_with_offset, FieldElement, StarkField};
+
+const SUMMING_DOMAIN_SIZE: usize = 16;
+
+fn get_sumcheck_proof() -> (SumcheckProof<BaseElement, BaseElement, Rp64_256>, BaseElement) {
+    let eta = BaseElement::GENERATOR;
+    let summing_domain = get_power_series_with_offset(
+        BaseElement::get_root_of_unity(4),
+        eta,
+        SUMMING_DOMAIN_SIZE,
+    );
+    let evaluation_domain = get_power_series(BaseElement::get_root_of_unity(6), 64);
+    let numerator_coeffs = get_numerator_coeffs();
+    let sigma = summing_domain
+        .iter()
+        .fold(BaseElement::ZERO, |sum, &x| sum + polynom::eval(&numerator_coeffs, x));
+
+    let mut prover = RationalSumcheckProver::<BaseElement, BaseElement, Rp64_256>::new(
+        numerator_coeffs,
+        vec![BaseElement::ONE],
+        sigma,
+        summing_domain,
+        eta,
+        evaluation_domain,
+        SUMMING_DOMAIN_SIZE - 2,
+        SUMMING_DOMAIN_SIZE - 1,
+        FriOptions::new(4, 4, 32),
+        16,
+    );
+    (prover.generate_proof().unwrap(), sigma)
+}
+
+fn get_numerator_coeffs() -> Vec<BaseElement> {
+    (1..10u64).map(BaseElement::new).collect()
+}
+
+fn verify(
+    proof: SumcheckProof<BaseElement, BaseElement, Rp64_256>,
+    sigma: BaseElement,
+) -> Result<(), SumcheckVerifierError> {
+    let options = ProofOptions::new(FriOptions::new(4, 4, 32), 16);
+    verify_sumcheck_proof(proof, SUMMING_DOMAIN_SIZE - 2, SUMMING_DOMAIN_SIZE - 1, sigma, &options)
+}
+
+#[test]
+fn test_sumcheck_proof_verifies() {
+    let (proof, sigma) = get_sumcheck_proof();
+    assert!(verify(proof, sigma).is_ok());
+}
+
+#[test]
+fn test_reject_mismatched_sumcheck_options() {
+    let (mut proof, sigma) = get_sumcheck_proof();
+    proof.options = FriOptions::new(8, 4, 32);
+    assert!(matches!(
+        verify(proof, sigma),
+        Err(SumcheckVerifierError::OptionsMismatchErr(_))
+    ));
+
+    let (mut proof, sigma) = get_sumcheck_proof();
+    proof.num_evaluations = 128;
+    assert!(matches!(
+        verify(proof, sigma),
+        Err(SumcheckVerifierError::OptionsMismatchErr(_))
+    ));
+
+    // the verifier's options decide, not the ones the proof was made with
+    let (proof, sigma) = get_sumcheck_proof();
+    let degrees = (SUMMING_DOMAIN_SIZE - 2, SUMMING_DOMAIN_SIZE - 1);
+    let other_folding = ProofOptions::new(FriOptions::new(4, 8, 32), 16);
+    assert!(matches!(
+        verify_sumcheck_proof_ref(&proof, degrees.0, degrees.1, sigma, &other_folding),
+        Err(SumcheckVerifierError::OptionsMismatchErr(_))
+    ));
+    let more_queries = ProofOptions::new(FriOptions::new(4, 4, 32), 32);
+    assert_eq!(
+        verify_sumcheck_proof_ref(&proof, degrees.0, degrees.1, sigma, &more_queries),
+        Err(SumcheckVerifierError::PolynomialErr(
+            SumcheckPolynomial::G,
+            LowDegreeVerifierError::QueryPositionsMismatch
+        ))
+    );
+}
+
+#[test]
+fn test_sumcheck_identity_at_queried_positions() {
+    let (mut proof, sigma) = get_sumcheck_proof();
+    let eval_domain_base = BaseElement::get_root_of_unity(6);
+    let numerator_evals: Vec<BaseElement> = proof
+        .e_proof
+        .queried_positions
+        .iter()
+        .map(|&p| polynom::eval(&get_numerator_coeffs(), eval_domain_base.exp(p as u64)))
+        .collect();
+    let denominator_evals = vec![BaseElement::ONE; numerator_evals.len()];
+    let verify_queries = |proof: &SumcheckProof<BaseElement, BaseElement, Rp64_256>, sigma| {
+        verify_sumcheck_queries(
+            proof,
+            SUMMING_DOMAIN_SIZE,
+            BaseElement::GENERATOR,
+            sigma,
+            &numerator_evals,
+            &denominator_evals,
+        )
+    };
+    assert_eq!(verify_queries(&proof, sigma), Ok(()));
+
+    let position = proof.e_proof.queried_positions[0];
+    assert_eq!(
+        verify_queries(&proof, sigma + BaseElement::ONE),
+        Err(SumcheckVerifierError::IdentityErr(position))
+    );
+    assert_eq!(
+        verify_sumcheck_queries(
+            &proof,
+            SUMMING_DOMAIN_SIZE,
+            BaseElement::GENERATOR,
+            sigma,
+            &numerator_evals[1..],
+            &denominator_evals[1..],
+        ),
+        Err(SumcheckVerifierError::QueriesMismatchErr)
+    );
+
+    proof.g_queried.queried_evals[0] += BaseElement::ONE;
+    assert_eq!(
+        verify_queries(&proof, sigma),
+        Err(SumcheckVerifierError::GOpeningErr(position))
+    );
+}
+
+#[test]
+fn test_sumcheck_proof_serialization() {
+    let (proof, _) = get_sumcheck_proof();
+    let g_bytes = proof.g_proof.to_bytes();
+    let e_bytes = proof.e_proof.to_bytes();
+    let g_queried_bytes = proof.g_queried.to_bytes();
+    let bytes = proof.to_bytes();
+    assert!(bytes.len() > g_bytes.len() + e_bytes.len() + g_queried_bytes.len());
+
+    let mut reader = SliceReader::new(&bytes);
+    assert_eq!(reader.read_u32().unwrap() as usize, proof.options.blowup_factor());
+    assert_eq!(reader.read_u32().unwrap() as usize, proof.options.folding_factor());
+    assert_eq!(reader.read_u32().unwrap() as usize, proof.options.max_remainder_size());
+    assert_eq!(reader.read_u32().unwrap() as usize, proof.num_evaluations);
+    assert_eq!(reader.read_u32().unwrap() as usize, proof.queried_positions.len());
+    for &pos in proof.queried_positions.iter() {
+        assert_eq!(reader.read_u32().unwrap() as usize, pos);
+    }
+    assert_eq!(reader.read_u8_vec(g_bytes.len()).unwrap(), g_bytes);
+    assert_eq!(reader.read_u32().unwrap() as usize, proof.g_max_degree);
+    assert_eq!(reader.read_u8_vec(e_bytes.len()).unwrap(), e_bytes);
+    assert_eq!(reader.read_u32().unwrap() as usize, proof.e_max_degree);
+    assert_eq!(reader.read_u8_vec(g_queried_bytes.len()).unwrap(), g_queried_bytes);
+    assert!(!reader.has_more_bytes());
+}
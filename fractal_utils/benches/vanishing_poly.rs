@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fractal_utils::polynomial_utils::{compute_vanishing_poly, VanishingPolyEvaluator};
+use winter_math::{fields::f64::BaseElement, FieldElement, StarkField};
+
+const DOMAIN_SIZE: usize = 1 << 16;
+const NUM_POINTS: [usize; 2] = [32, 1024];
+
+// Evaluates v_K at a batch of points, like the sumcheck verifier does at its queried positions.
+// compute_vanishing_poly takes two exponentiations per point, VanishingPolyEvaluator one per
+// point plus one for eta^|K|.
+fn vanishing_poly(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vanishing_poly");
+    let eta = BaseElement::GENERATOR;
+
+    for &num_points in NUM_POINTS.iter() {
+        let points: Vec<BaseElement> =
+            (0..num_points as u64).map(|i| BaseElement::new(i * i + 7)).collect();
+        group.bench_with_input(
+            BenchmarkId::new("compute_vanishing_poly", num_points),
+            &points,
+            |bench, points| {
+                bench.iter(|| {
+                    points
+                        .iter()
+                        .map(|&x| compute_vanishing_poly(x, black_box(eta), DOMAIN_SIZE))
+                        .fold(BaseElement::ZERO, |acc, v| acc + v)
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("evaluator", num_points),
+            &points,
+            |bench, points| {
+                bench.iter(|| {
+                    let v_k = VanishingPolyEvaluator::new(black_box(eta), DOMAIN_SIZE);
+                    points.iter().map(|&x| v_k.eval(x)).fold(BaseElement::ZERO, |acc, v| acc + v)
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(vanishing_poly_group, vanishing_poly);
+criterion_main!(vanishing_poly_group);
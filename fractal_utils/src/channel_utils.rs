@@ -0,0 +1,83 @@
+use winter_crypto::{ElementHasher, RandomCoin, RandomCoinError};
+use winter_fri::{DefaultProverChannel, ProverChannel};
+use winter_math::{FieldElement, StarkField};
+use winter_utils::{ByteReader, DeserializationError, SliceReader};
+
+/// A transcript field element challenges are drawn from: the verifier's `RandomCoin`, or the
+/// prover's `DefaultProverChannel`, which draws from a `RandomCoin` of its own. Both sides
+/// reseed with the same commitments, so they draw the same challenges.
+pub trait ChallengeSource<E: FieldElement> {
+    fn draw_element(&mut self) -> Result<E, RandomCoinError>;
+}
+
+impl<B, E, H> ChallengeSource<E> for RandomCoin<B, H>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    fn draw_element(&mut self) -> Result<E, RandomCoinError> {
+        self.draw()
+    }
+}
+
+impl<B, E, H> ChallengeSource<E> for DefaultProverChannel<B, E, H>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    fn draw_element(&mut self) -> Result<E, RandomCoinError> {
+        Ok(self.draw_fri_alpha())
+    }
+}
+
+/// Draws a field element challenge from `transcript`. Every challenge this crate draws itself,
+/// i.e. the lincheck's alpha and beta, goes through here, so prover and verifier use the same
+/// convention. This adds no sampling of its own: both transcripts draw with `RandomCoin::draw`,
+/// which takes the first `E::ELEMENT_BYTES` bytes of the next digest and, if they don't encode
+/// an element of `E`, moves on to the next digest, up to 1000 times. FRI folding challenges
+/// and query positions are drawn inside winter-fri.
+pub fn hash_to_field<E, C>(transcript: &mut C) -> Result<E, RandomCoinError>
+where
+    E: FieldElement,
+    C: ChallengeSource<E>,
+{
+    transcript.draw_element()
+}
+
+/// Draws the lincheck's beta after the product sumcheck. The matrix polynomials have base
+/// field coefficients, so beta is the first base field coordinate of an element of `E`; for
+/// `E = B` that's the element itself. Prover and verifier both draw beta through this.
+pub fn draw_lincheck_beta<B, E, C>(transcript: &mut C) -> Result<B, RandomCoinError>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    C: ChallengeSource<E>,
+{
+    let challenge: E = hash_to_field(transcript)?;
+    Ok(E::as_base_elements(&[challenge])[0])
+}
+
+/// Encodes the public inputs, i.e. a prefix of the variable assignment, as the bytes the prover
+/// and verifier seed their transcripts with. Each element is written in its canonical form, so
+/// [public_inputs_from_bytes] can read them back.
+pub fn public_inputs_to_bytes<B: StarkField>(public_inputs: &[B]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(public_inputs.len() * B::ELEMENT_BYTES);
+    B::write_batch_into(public_inputs, &mut bytes);
+    bytes
+}
+
+/// Reads `num_public_inputs` elements encoded by [public_inputs_to_bytes]. Fails if there are
+/// too few or too many bytes, or if an element isn't canonical.
+pub fn public_inputs_from_bytes<B: StarkField>(
+    bytes: &[u8],
+    num_public_inputs: usize,
+) -> Result<Vec<B>, DeserializationError> {
+    let mut reader = SliceReader::new(bytes);
+    let public_inputs = B::read_batch_from(&mut reader, num_public_inputs)?;
+    if reader.has_more_bytes() {
+        return Err(DeserializationError::UnconsumedBytes);
+    }
+    Ok(public_inputs)
+}
@@ -1,3 +1,4 @@
+pub mod channel_utils;
 pub mod errors;
 pub mod matrix_utils;
 pub mod polynomial_utils;
@@ -8,3 +9,14 @@ pub type SmallFieldElement13 = fractal_math::smallprimefield::BaseElement<13, 2,
 
 pub static BLOWUP_FACTOR: usize = 8;
 pub static FOLDING_FACTOR: usize = 4;
+
+/// The default size below which a domain is cheaper to process on one thread than to split
+/// across threads. Provers take their threshold from `FractalOptions::par_threshold`.
+pub static DEFAULT_PAR_THRESHOLD: usize = 1 << 10;
+
+/// Whether a parallel path should be taken for a domain of `len` elements, given the smallest
+/// domain worth splitting. Only matters for crates built with their `concurrent` feature;
+/// everything is serial otherwise.
+pub fn should_parallelize(len: usize, par_threshold: usize) -> bool {
+    len >= par_threshold
+}
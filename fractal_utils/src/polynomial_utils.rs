@@ -1,5 +1,5 @@
 use crate::matrix_utils::*;
-use fractal_math::FieldElement;
+use fractal_math::{FieldElement, StarkField};
 use std::convert::TryInto;
 // TODO: Add error checking and throwing
 /**
@@ -14,6 +14,31 @@ pub fn compute_vanishing_poly<E: FieldElement>(x: E, eta: E, dom_size: usize) ->
     x.exp(power) - eta.exp(power)
 }
 
+/**
+ * Evaluates v_H(X) = X^dom_size - eta^dom_size like compute_vanishing_poly, but
+ * computes eta^dom_size once up front. Use it when evaluating the same v_H at
+ * many points, e.g. over a whole evaluation domain.
+ **/
+#[derive(Clone, Copy, Debug)]
+pub struct VanishingPolyEvaluator<E: FieldElement> {
+    dom_size: u64,
+    eta_to_dom_size: E,
+}
+
+impl<E: FieldElement> VanishingPolyEvaluator<E> {
+    pub fn new(eta: E, dom_size: usize) -> Self {
+        let dom_size: u64 = dom_size.try_into().unwrap();
+        VanishingPolyEvaluator {
+            dom_size,
+            eta_to_dom_size: eta.exp(E::PositiveInteger::from(dom_size)),
+        }
+    }
+
+    pub fn eval(&self, x: E) -> E {
+        x.exp(E::PositiveInteger::from(self.dom_size)) - self.eta_to_dom_size
+    }
+}
+
 /**
  * Compute vanishing polynomial for a multiplicative subgroup. Same as above with
  * eta = ONE.
@@ -89,3 +114,46 @@ pub fn get_complementary_poly<E: FieldElement>(
     out_poly[0] = E::ONE;
     out_poly
 }
+
+/// Debugging helper: interpolates the polynomial through the queried evaluations `evals`,
+/// where `positions` index into the evaluation domain `offset * <g>` of size `eval_domain_size`.
+/// The result has degree below `positions.len()`, so it only says something about the committed
+/// polynomial when there are more queries than its degree, e.g. to see that a polynomial which
+/// failed a low degree check really does have high degree.
+pub fn interpolate_from_queries<B: StarkField, E: FieldElement<BaseField = B>>(
+    positions: &[usize],
+    evals: &[E],
+    eval_domain_size: usize,
+    offset: E,
+) -> Vec<E> {
+    assert_eq!(positions.len(), evals.len(), "need one evaluation per position");
+    let domain_base = B::get_root_of_unity(eval_domain_size.trailing_zeros());
+    let xs: Vec<E> = positions
+        .iter()
+        .map(|&pos| offset * E::from(domain_base.exp((pos as u64).into())))
+        .collect();
+    fractal_math::polynom::interpolate(&xs, evals, true)
+}
+
+/// The polynomials tying the public inputs to z. z is interpolated over eta * <g>, where g has
+/// order `z_domain_size`, so the public inputs x are z's values on X = {eta * g^i : i < x.len()}.
+/// Returns x_hat, which interpolates x over X, and the vanishing polynomial v_X of X. z starts
+/// with x exactly when z - x_hat is divisible by v_X.
+pub fn public_input_polys<B: StarkField>(
+    public_inputs: &[B],
+    eta: B,
+    z_domain_size: usize,
+) -> (Vec<B>, Vec<B>) {
+    if public_inputs.is_empty() {
+        return (vec![B::ZERO], vec![B::ONE]);
+    }
+    let g = B::get_root_of_unity(z_domain_size.trailing_zeros());
+    let xs: Vec<B> = (0..public_inputs.len())
+        .map(|i| eta * g.exp((i as u64).into()))
+        .collect();
+    let x_hat = fractal_math::polynom::interpolate(&xs, public_inputs, true);
+    let v_x = xs
+        .iter()
+        .fold(vec![B::ONE], |v_x, &x| fractal_math::polynom::mul(&v_x, &[-x, B::ONE]));
+    (x_hat, v_x)
+}
@@ -1,5 +1,113 @@
-use crate::{errors::MatrixError, matrix_utils::*, SmallFieldElement17};
+use crate::{errors::MatrixError, matrix_utils::*, should_parallelize, SmallFieldElement17, DEFAULT_PAR_THRESHOLD};
 use fractal_math::{FieldElement, StarkField};
+use crate::channel_utils::{
+    draw_lincheck_beta, hash_to_field, public_inputs_from_bytes, public_inputs_to_bytes,
+};
+use crate::polynomial_utils::{
+    compute_vanishing_poly, interpolate_from_queries, public_input_polys, VanishingPolyEvaluator,
+};
+use winter_crypto::{hashers::Rp64_256, Hasher, RandomCoin};
+use winter_fri::{DefaultProverChannel, ProverChannel};
+use winter_math::fields::{f64::BaseElement, QuadExtension};
+
+#[test]
+fn test_hash_to_field_matches_on_both_sides() {
+    let pub_inputs_bytes = vec![1u8, 2, 3];
+    let mut prover_coin = RandomCoin::<BaseElement, Rp64_256>::new(&pub_inputs_bytes);
+    let mut verifier_coin = RandomCoin::<BaseElement, Rp64_256>::new(&pub_inputs_bytes);
+    for _ in 0..4 {
+        let prover_challenge: BaseElement = hash_to_field(&mut prover_coin).unwrap();
+        let verifier_challenge: BaseElement = hash_to_field(&mut verifier_coin).unwrap();
+        assert_eq!(prover_challenge, verifier_challenge);
+    }
+
+    let mut other_coin = RandomCoin::<BaseElement, Rp64_256>::new(&[4u8]);
+    let other_challenge: BaseElement = hash_to_field(&mut other_coin).unwrap();
+    let mut prover_coin = RandomCoin::<BaseElement, Rp64_256>::new(&pub_inputs_bytes);
+    let prover_challenge: BaseElement = hash_to_field(&mut prover_coin).unwrap();
+    assert_ne!(prover_challenge, other_challenge);
+
+    // the prover's FRI channel draws the same challenges as a coin with the same commitments
+    let mut channel = DefaultProverChannel::<BaseElement, BaseElement, Rp64_256>::new(64, 4);
+    let mut coin = RandomCoin::<BaseElement, Rp64_256>::new(&[]);
+    let commitment = Rp64_256::hash(&pub_inputs_bytes);
+    channel.commit_fri_layer(commitment);
+    coin.reseed(commitment);
+    for _ in 0..4 {
+        let prover_challenge: BaseElement = hash_to_field(&mut channel).unwrap();
+        let verifier_challenge: BaseElement = hash_to_field(&mut coin).unwrap();
+        assert_eq!(prover_challenge, verifier_challenge);
+    }
+}
+
+#[test]
+fn test_lincheck_beta_matches_over_extension_field() {
+    type Quad = QuadExtension<BaseElement>;
+    let commitments = [Rp64_256::hash(&[1u8]), Rp64_256::hash(&[2u8])];
+    let mut channel = DefaultProverChannel::<BaseElement, Quad, Rp64_256>::new(64, 4);
+    let mut coin = RandomCoin::<BaseElement, Rp64_256>::new(&[]);
+    for commitment in commitments {
+        channel.commit_fri_layer(commitment);
+        coin.reseed(commitment);
+        let prover_beta = draw_lincheck_beta::<BaseElement, Quad, _>(&mut channel).unwrap();
+        let verifier_beta = draw_lincheck_beta::<BaseElement, Quad, _>(&mut coin).unwrap();
+        assert_eq!(prover_beta, verifier_beta);
+    }
+}
+
+#[test]
+fn test_interpolate_from_queries() {
+    let poly: Vec<BaseElement> = (1..7u64).map(BaseElement::new).collect();
+    let eval_domain_size = 64;
+    let offset = BaseElement::GENERATOR;
+    let domain = winter_math::get_power_series_with_offset(
+        BaseElement::get_root_of_unity(6),
+        offset,
+        eval_domain_size,
+    );
+    let evals = fractal_math::polynom::eval_many(&poly, &domain);
+
+    let positions = vec![1, 5, 9, 20, 33, 47, 58, 63];
+    let queried_evals: Vec<BaseElement> = positions.iter().map(|&pos| evals[pos]).collect();
+    let interpolated =
+        interpolate_from_queries(&positions, &queried_evals, eval_domain_size, offset);
+    assert_eq!(interpolated, poly);
+}
+
+#[test]
+fn test_public_inputs_round_trip() {
+    let public_inputs: Vec<BaseElement> = (3..7u64).map(BaseElement::new).collect();
+    let bytes = public_inputs_to_bytes(&public_inputs);
+    assert_eq!(public_inputs_from_bytes::<BaseElement>(&bytes, 4).unwrap(), public_inputs);
+    assert!(public_inputs_from_bytes::<BaseElement>(&bytes, 3).is_err());
+    assert!(public_inputs_from_bytes::<BaseElement>(&bytes, 5).is_err());
+}
+
+#[test]
+fn test_public_input_polys() {
+    let z_domain_size = 16;
+    let eta = BaseElement::GENERATOR;
+    let z: Vec<BaseElement> = (0..z_domain_size as u64).map(|i| BaseElement::new(i * i + 1)).collect();
+    let z_domain = winter_math::get_power_series_with_offset(
+        BaseElement::get_root_of_unity(4),
+        eta,
+        z_domain_size,
+    );
+    let z_coeffs = fractal_math::polynom::interpolate(&z_domain, &z, true);
+
+    let (x_hat, v_x) = public_input_polys(&z[..3], eta, z_domain_size);
+    for i in 0..3 {
+        assert_eq!(fractal_math::polynom::eval(&x_hat, z_domain[i]), z[i]);
+        assert_eq!(fractal_math::polynom::eval(&v_x, z_domain[i]), BaseElement::ZERO);
+    }
+    assert_ne!(fractal_math::polynom::eval(&v_x, z_domain[3]), BaseElement::ZERO);
+    // z - x_hat vanishes on X, so v_X divides it
+    let w = fractal_math::polynom::div(&fractal_math::polynom::sub(&z_coeffs, &x_hat), &v_x);
+    let recombined = fractal_math::polynom::add(&x_hat, &fractal_math::polynom::mul(&v_x, &w));
+    assert_eq!(fractal_math::polynom::remove_leading_zeros(&recombined), z_coeffs);
+
+    assert_eq!(public_input_polys(&[], eta, z_domain_size), (vec![BaseElement::ZERO], vec![BaseElement::ONE]));
+}
 
 #[test]
 fn test_matrix_star() {
@@ -32,3 +140,27 @@ fn make_all_ones_matrix_f17(
     }
     Matrix::new(matrix_name, mat)
 }
+
+#[test]
+fn test_should_parallelize_threshold() {
+    assert!(!should_parallelize(1 << 8, DEFAULT_PAR_THRESHOLD));
+    assert!(!should_parallelize(DEFAULT_PAR_THRESHOLD - 1, DEFAULT_PAR_THRESHOLD));
+    assert!(should_parallelize(DEFAULT_PAR_THRESHOLD, DEFAULT_PAR_THRESHOLD));
+    assert!(should_parallelize(DEFAULT_PAR_THRESHOLD << 4, DEFAULT_PAR_THRESHOLD));
+    // the threshold is a parameter, not a constant
+    assert!(should_parallelize(1 << 8, 1 << 8));
+    assert!(!should_parallelize(1 << 12, usize::MAX));
+}
+
+#[test]
+fn test_vanishing_poly_evaluator() {
+    let eta = BaseElement::GENERATOR;
+    for dom_size in [1, 2, 16, 1024] {
+        let v_h = VanishingPolyEvaluator::new(eta, dom_size);
+        for i in 0..64u64 {
+            let x = BaseElement::new(i * 1_000_003 + 5);
+            assert_eq!(v_h.eval(x), compute_vanishing_poly(x, eta, dom_size));
+        }
+        assert_eq!(v_h.eval(eta), BaseElement::ZERO);
+    }
+}
@@ -8,6 +8,7 @@
 //! Errors for various data structure operations.
 use fractal_proofs::DeserializationError;
 use fractal_sumcheck::errors::SumcheckVerifierError;
+use winter_crypto::RandomCoinError;
 use winter_fri::VerifierError;
 
 #[cfg_attr(test, derive(PartialEq))]
@@ -17,6 +18,26 @@ pub enum LincheckVerifierError {
     UnsoundProduct(SumcheckVerifierError),
     /// Error propagation
     UnsoundMatrix(SumcheckVerifierError),
+    /// The proof's alpha is not the one drawn from the public inputs
+    AlphaMismatch,
+    /// The proof's beta is not the one drawn after the product sumcheck
+    BetaMismatch,
+    /// The low degree proof for (t_alpha - gamma) / (X - beta) is invalid
+    UnsoundGamma(low_degree::errors::LowDegreeVerifierError),
+    /// t_alpha isn't opened once at every position the gamma proof queries
+    TAlphaQueriesMismatch,
+    /// The t_alpha opening at this queried position doesn't match the committed t_alpha
+    TAlphaOpeningErr(usize),
+    /// t_alpha - gamma != (X - beta) * q at this queried position, i.e. gamma isn't t_alpha(beta)
+    GammaMismatch(usize),
+    /// row, col or val isn't opened once at every position the matrix sumcheck's e proof queries
+    MatrixQueriesMismatch,
+    /// The row, col or val opening at this queried position doesn't match the index
+    MatrixOpeningErr(usize),
+    /// The proof's FRI options aren't the verifier's
+    OptionsMismatch,
+    /// beta couldn't be drawn from the transcript
+    BetaDrawErr(RandomCoinError),
 }
 
 impl From<SumcheckVerifierError> for LincheckVerifierError {
@@ -34,6 +55,36 @@ impl std::fmt::Display for LincheckVerifierError {
             LincheckVerifierError::UnsoundMatrix(err) => {
                 writeln!(f, "Lincheck error: unsound matrix: {}", err)
             }
+            LincheckVerifierError::AlphaMismatch => {
+                writeln!(f, "Lincheck error: alpha does not match the transcript")
+            }
+            LincheckVerifierError::BetaMismatch => {
+                writeln!(f, "Lincheck error: beta does not match the transcript")
+            }
+            LincheckVerifierError::UnsoundGamma(err) => {
+                writeln!(f, "Lincheck error: unsound gamma: {}", err)
+            }
+            LincheckVerifierError::TAlphaQueriesMismatch => {
+                writeln!(f, "Lincheck error: t_alpha openings don't match the gamma proof's queries")
+            }
+            LincheckVerifierError::TAlphaOpeningErr(position) => {
+                writeln!(f, "Lincheck error: t_alpha opening at position {} is invalid", position)
+            }
+            LincheckVerifierError::GammaMismatch(position) => {
+                writeln!(f, "Lincheck error: gamma is not t_alpha(beta), checked at position {}", position)
+            }
+            LincheckVerifierError::MatrixQueriesMismatch => {
+                writeln!(f, "Lincheck error: row, col and val openings don't match the matrix sumcheck's queries")
+            }
+            LincheckVerifierError::MatrixOpeningErr(position) => {
+                writeln!(f, "Lincheck error: row, col or val opening at position {} is invalid", position)
+            }
+            LincheckVerifierError::OptionsMismatch => {
+                writeln!(f, "Lincheck error: FRI options don't match the verifier's")
+            }
+            LincheckVerifierError::BetaDrawErr(err) => {
+                writeln!(f, "Lincheck error: failed to draw beta: {}", err)
+            }
         }
     }
 }
@@ -49,6 +100,24 @@ pub enum RowcheckVerifierError {
     SmallPolyAdjustmentErr(),
     /// Error propagation
     FriVerifierErr(winter_fri::VerifierError),
+    /// The queried positions aren't the ones drawn after committing to f and s
+    QueryPositionsMismatch,
+    /// A queried value vector doesn't have one entry per queried position
+    QueriedValuesLengthMismatch,
+    /// The opened leaf at this queried position doesn't hash the queried values
+    LeafMismatch(usize),
+    /// s * v_H differs from f_Az * f_Bz - f_Cz at this queried position
+    QuotientMismatch(usize),
+    /// Error propagation
+    LowDegreeVerifierErr(low_degree::errors::LowDegreeVerifierError),
+    /// f_z differs from x_hat + v_X * w at this queried position, i.e. z doesn't start with the
+    /// public inputs
+    PublicInputsMismatch(usize),
+    /// The proof's FRI options aren't the verifier's
+    OptionsMismatch,
+    /// The rowcheck was made over an evaluation domain of a different size than the verifier
+    /// key and the FRI blowup factor imply
+    EvaluationDomainMismatch { expected: usize, actual: usize },
 }
 
 impl From<winter_utils::DeserializationError> for RowcheckVerifierError {
@@ -62,6 +131,12 @@ impl From<winter_crypto::MerkleTreeError> for RowcheckVerifierError {
     }
 }
 
+impl From<low_degree::errors::LowDegreeVerifierError> for RowcheckVerifierError {
+    fn from(error: low_degree::errors::LowDegreeVerifierError) -> Self {
+        Self::LowDegreeVerifierErr(error)
+    }
+}
+
 impl From<winter_fri::VerifierError> for RowcheckVerifierError {
     fn from(error: winter_fri::VerifierError) -> Self {
         Self::FriVerifierErr(error)
@@ -83,6 +158,34 @@ impl std::fmt::Display for RowcheckVerifierError {
             RowcheckVerifierError::FriVerifierErr(err) => {
                 writeln!(f, "Rowcheck Fri error: {}", err)
             }
+            RowcheckVerifierError::QueryPositionsMismatch => {
+                writeln!(f, "Rowcheck queried positions don't match the transcript")
+            }
+            RowcheckVerifierError::QueriedValuesLengthMismatch => {
+                writeln!(f, "Rowcheck queried values don't match the queried positions")
+            }
+            RowcheckVerifierError::LeafMismatch(position) => {
+                writeln!(f, "Rowcheck leaf at position {} doesn't match the queried values", position)
+            }
+            RowcheckVerifierError::QuotientMismatch(position) => {
+                writeln!(f, "Rowcheck s * v_H != f_Az * f_Bz - f_Cz at position {}", position)
+            }
+            RowcheckVerifierError::LowDegreeVerifierErr(err) => {
+                writeln!(f, "Rowcheck low degree error for w: {}", err)
+            }
+            RowcheckVerifierError::PublicInputsMismatch(position) => {
+                writeln!(f, "Rowcheck f_z != x_hat + v_X * w at position {}", position)
+            }
+            RowcheckVerifierError::OptionsMismatch => {
+                writeln!(f, "Rowcheck FRI options don't match the verifier's")
+            }
+            RowcheckVerifierError::EvaluationDomainMismatch { expected, actual } => {
+                writeln!(
+                    f,
+                    "Rowcheck is over an evaluation domain of size {}, but the verifier expects {}",
+                    actual, expected
+                )
+            }
         }
     }
 }
@@ -94,6 +197,25 @@ pub enum FractalVerifierError {
     LincheckVerifierErr(LincheckVerifierError),
     /// Error propagation
     RowcheckVerifierErr(RowcheckVerifierError),
+    /// The verifier key does not open the expected key commitment
+    VerifierKeyCommitmentErr,
+    /// The proof was made over an evaluation domain of a different size than the verifier
+    /// key implies, i.e. for a different circuit
+    CircuitSizeMismatch { expected: usize, actual: usize },
+    /// pub_inputs_bytes don't encode the number of public inputs the verifier key declares
+    PublicInputsLengthMismatch { expected: usize, actual: usize },
+    /// The proof was generated for different public inputs than the verifier was given
+    PublicInputMismatch,
+    /// A part of the proof has the wrong number of queries or FRI layers
+    MalformedProof(String),
+    /// This part of the proof uses other FRI options than the verifier's
+    OptionsMismatch(String),
+    /// The proof was generated over a different base field or extension than the verifier's
+    FieldMismatch,
+    /// Error propagation
+    DeserializationErr(DeserializationError),
+    /// alpha couldn't be drawn from the transcript
+    AlphaDrawErr(RandomCoinError),
 }
 
 impl From<LincheckVerifierError> for FractalVerifierError {
@@ -108,6 +230,12 @@ impl From<RowcheckVerifierError> for FractalVerifierError {
     }
 }
 
+impl From<DeserializationError> for FractalVerifierError {
+    fn from(error: DeserializationError) -> Self {
+        Self::DeserializationErr(error)
+    }
+}
+
 impl std::fmt::Display for FractalVerifierError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
@@ -117,6 +245,41 @@ impl std::fmt::Display for FractalVerifierError {
             FractalVerifierError::RowcheckVerifierErr(err) => {
                 writeln!(f, "Rowcheck error: {}", err)
             }
+            FractalVerifierError::VerifierKeyCommitmentErr => {
+                writeln!(f, "Verifier key does not match the key commitment")
+            }
+            FractalVerifierError::CircuitSizeMismatch { expected, actual } => {
+                writeln!(
+                    f,
+                    "Proof is over an evaluation domain of size {}, but the verifier key expects {}",
+                    actual, expected
+                )
+            }
+            FractalVerifierError::PublicInputsLengthMismatch { expected, actual } => {
+                writeln!(
+                    f,
+                    "Expected {} bytes of public inputs, but got {}",
+                    expected, actual
+                )
+            }
+            FractalVerifierError::PublicInputMismatch => {
+                writeln!(f, "Proof was generated for different public inputs")
+            }
+            FractalVerifierError::MalformedProof(err) => {
+                writeln!(f, "Malformed proof: {}", err)
+            }
+            FractalVerifierError::OptionsMismatch(what) => {
+                writeln!(f, "FRI options of the {} differ from the verifier's", what)
+            }
+            FractalVerifierError::FieldMismatch => {
+                writeln!(f, "Proof was generated over a different field configuration")
+            }
+            FractalVerifierError::DeserializationErr(err) => {
+                writeln!(f, "Proof deserialization error: {}", err)
+            }
+            FractalVerifierError::AlphaDrawErr(err) => {
+                writeln!(f, "Failed to draw alpha: {}", err)
+            }
         }
     }
 }
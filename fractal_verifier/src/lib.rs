@@ -1,8 +1,14 @@
 pub mod errors;
 mod lincheck_verifier;
 mod rowcheck_verifier;
+#[cfg(test)]
 mod tests;
 pub mod verifier;
 
+pub use lincheck_verifier::{verify_lincheck_proof, verify_lincheck_proof_ref};
+pub use rowcheck_verifier::{
+    verify_rowcheck_proof, verify_rowcheck_proof_ref, verify_s_padding_all, verify_s_quotient_all,
+};
+
 pub use fractal_sumcheck;
 pub use fractal_indexer;
\ No newline at end of file
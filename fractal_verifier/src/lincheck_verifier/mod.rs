@@ -1,49 +1,208 @@
 use crate::errors::LincheckVerifierError;
 
-use fractal_indexer::snark_keys::VerifierKey;
-use fractal_proofs::{FieldElement, LincheckProof};
-use fractal_sumcheck::{sumcheck_verifier::verify_sumcheck_proof, log::debug};
+use fractal_indexer::snark_keys::{VerifierKey, VerifierMatrixIndex};
+use fractal_proofs::{FieldElement, LincheckProof, OracleQueries, ProofOptions};
+use fractal_utils::channel_utils::draw_lincheck_beta;
+use fractal_utils::polynomial_utils::VanishingPolyEvaluator;
+use fractal_sumcheck::{
+    log::debug,
+    sumcheck_verifier::{
+        verify_sumcheck_proof_ref, verify_sumcheck_proof_with_coin_ref, verify_sumcheck_queries,
+    },
+};
 
-use winter_crypto::{ElementHasher};
+use low_degree::low_degree_verifier::{get_queried_domain_elements, verify_low_degree_proof_ref};
+use winter_crypto::{ElementHasher, MerkleTree, RandomCoin};
 use winter_math::StarkField;
 
+/// Verifies the lincheck for one matrix, whose index is committed to in `matrix_commitments`.
+/// `expected_alpha` is the alpha the verifier drew; the proof must use it.
 pub fn verify_lincheck_proof<
     B: StarkField,
     E: FieldElement<BaseField = B>,
     H: ElementHasher<BaseField = B>,
 >(
     verifier_key: &VerifierKey<H, B>,
+    options: &ProofOptions,
+    matrix_commitments: &VerifierMatrixIndex<H, B>,
     proof: LincheckProof<B, E, H>,
-    _expected_alpha: B,
+    expected_alpha: B,
 ) -> Result<(), LincheckVerifierError> {
+    verify_lincheck_proof_ref(verifier_key, options, matrix_commitments, &proof, expected_alpha)
+}
 
-    let _alpha = proof.alpha;
-    debug!("verifier alpha: {}", &_alpha);
-    let _t_alpha_commitment = proof.t_alpha_commitment;
-    let _t_alpha_queried = proof.t_alpha_queried;
-    
-    let products_sumcheck_proof = proof.products_sumcheck_proof;
+/// Same as [verify_lincheck_proof], but borrows the proof so it can be verified again or
+/// inspected afterwards.
+pub fn verify_lincheck_proof_ref<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    options: &ProofOptions,
+    matrix_commitments: &VerifierMatrixIndex<H, B>,
+    proof: &LincheckProof<B, E, H>,
+    expected_alpha: B,
+) -> Result<(), LincheckVerifierError> {
+    if proof.options != options.fri_options {
+        return Err(LincheckVerifierError::OptionsMismatch);
+    }
+    let alpha = proof.alpha;
+    debug!("verifier alpha: {}", &alpha);
+    if alpha != expected_alpha {
+        return Err(LincheckVerifierError::AlphaMismatch);
+    }
+    let products_sumcheck_proof = &proof.products_sumcheck_proof;
     debug!("Lincheck verifier indexes: {:?}", &products_sumcheck_proof.queried_positions);
 
     let h_field_size = std::cmp::max(verifier_key.params.num_input_variables, verifier_key.params.num_constraints);
     let g_degree = h_field_size - 2;
     let e_degree = h_field_size - 1;
-    verify_sumcheck_proof(products_sumcheck_proof, g_degree, e_degree)
+    let mut public_coin = RandomCoin::new(&[]);
+    // The prover commits to t_alpha before the product sumcheck, so it's fixed before beta.
+    public_coin.reseed(proof.t_alpha_commitment);
+    verify_sumcheck_proof_with_coin_ref(products_sumcheck_proof, g_degree, e_degree, B::ZERO, options, &mut public_coin)
     .map_err(|err| LincheckVerifierError::UnsoundProduct(err))?;
+    // The prover draws beta from its product sumcheck channel right after that proof.
+    let expected_beta = draw_lincheck_beta::<B, E, _>(&mut public_coin).map_err(LincheckVerifierError::BetaDrawErr)?;
+    if proof.beta != expected_beta {
+        return Err(LincheckVerifierError::BetaMismatch);
+    }
+    verify_gamma(proof, h_field_size, options, &mut public_coin)?;
 
+    // FIXME: f_Mz and f_z aren't opened where the product sumcheck's e is queried, so its
+    // identity isn't checked yet.
     debug!("Verified sumcheck for product");
-    let _row_queried = proof.row_queried;
-    let _col_queried = proof.col_queried;
-    let _val_queried = proof.val_queried;
 
-    let matrix_sumcheck_proof = proof.matrix_sumcheck_proof;
+    let matrix_sumcheck_proof = &proof.matrix_sumcheck_proof;
     let k_field_size = verifier_key.params.num_non_zero;
     let g_degree = k_field_size - 2;
     let e_degree = 2 * k_field_size - 3;
-    verify_sumcheck_proof(matrix_sumcheck_proof, g_degree, e_degree)
+    verify_sumcheck_proof_ref(matrix_sumcheck_proof, g_degree, e_degree, proof.gamma, options)
     .map_err(|err| LincheckVerifierError::UnsoundMatrix(err))?;
-    // Need to do the checking of beta and channel passing etc.
-    // Also need to make sure that the queried evals are dealt with
+    verify_matrix_queries(verifier_key, matrix_commitments, proof, h_field_size, k_field_size)
+}
+
+/// Checks the row, col and val openings against the index, then the matrix sumcheck identity
+/// at the positions they are opened at. The summed polynomial is
+/// v_H(alpha) * v_H(beta) * val / ((alpha - col) * (beta - row)).
+fn verify_matrix_queries<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    matrix_commitments: &VerifierMatrixIndex<H, B>,
+    proof: &LincheckProof<B, E, H>,
+    h_field_size: usize,
+    k_field_size: usize,
+) -> Result<(), LincheckVerifierError> {
+    let matrix_sumcheck_proof = &proof.matrix_sumcheck_proof;
+    let positions = &matrix_sumcheck_proof.e_proof.queried_positions;
+    let row = verify_index_openings(&proof.row_queried, matrix_commitments.row_poly_commitment, positions, proof.num_evaluations)?;
+    let col = verify_index_openings(&proof.col_queried, matrix_commitments.col_poly_commitment, positions, proof.num_evaluations)?;
+    let val = verify_index_openings(&proof.val_queried, matrix_commitments.val_poly_commitment, positions, proof.num_evaluations)?;
+
+    let v_h = VanishingPolyEvaluator::new(verifier_key.params.eta, h_field_size);
+    let alpha = E::from(proof.alpha);
+    let beta = E::from(proof.beta);
+    let v_h_alpha_beta = E::from(v_h.eval(proof.alpha) * v_h.eval(proof.beta));
+    let numerator_evals: Vec<E> = val.iter().map(|&v| v_h_alpha_beta * v).collect();
+    let denominator_evals: Vec<E> =
+        col.iter().zip(row.iter()).map(|(&c, &r)| (alpha - c) * (beta - r)).collect();
+    verify_sumcheck_queries(
+        matrix_sumcheck_proof,
+        k_field_size,
+        verifier_key.params.eta_k,
+        proof.gamma,
+        &numerator_evals,
+        &denominator_evals,
+    )
+    .map_err(LincheckVerifierError::UnsoundMatrix)
+}
+
+/// Checks openings of a row, col or val polynomial of the index at `positions` against its
+/// commitment, and returns the opened values. The index commits to base field evaluations, so
+/// each opened value must be one.
+fn verify_index_openings<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    queries: &OracleQueries<B, E, H>,
+    commitment: H::Digest,
+    positions: &[usize],
+    num_evaluations: usize,
+) -> Result<Vec<E>, LincheckVerifierError> {
+    if queries.queried_evals.len() != positions.len() || queries.queried_proofs.len() != positions.len() {
+        return Err(LincheckVerifierError::MatrixQueriesMismatch);
+    }
+    // a path holds the leaf, its sibling and one node per level above them
+    let path_len = num_evaluations.trailing_zeros() as usize + 1;
+    for (i, &position) in positions.iter().enumerate() {
+        let path = &queries.queried_proofs[i];
+        let value = E::as_base_elements(&queries.queried_evals[i..i + 1]);
+        if value[1..].iter().any(|&coeff| coeff != B::ZERO)
+            || path.len() != path_len
+            || path[0] != H::hash_elements(&value[..1])
+            || MerkleTree::<H>::verify(commitment, position, path).is_err()
+        {
+            return Err(LincheckVerifierError::MatrixOpeningErr(position));
+        }
+    }
+    Ok(queries.queried_evals.clone())
+}
+
+/// Checks gamma = t_alpha(beta) against the committed t_alpha. The prover commits to gamma and
+/// proves q = (t_alpha - gamma) / (X - beta) has degree at most |H| - 2, which draws the
+/// positions t_alpha is opened at. At each of them the verifier checks the opening against
+/// t_alpha_commitment and that t_alpha(x) - gamma = q(x) * (x - beta). If gamma were not
+/// t_alpha(beta), t_alpha - gamma would have no such low degree quotient.
+fn verify_gamma<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: &LincheckProof<B, E, H>,
+    h_field_size: usize,
+    options: &ProofOptions,
+    public_coin: &mut RandomCoin<B, H>,
+) -> Result<(), LincheckVerifierError> {
+    let gamma_proof = &proof.gamma_proof;
+    public_coin.reseed(H::hash_elements(&[proof.gamma]));
+    verify_low_degree_proof_ref(gamma_proof, h_field_size - 2, options, public_coin)
+        .map_err(LincheckVerifierError::UnsoundGamma)?;
+
+    let positions = &gamma_proof.queried_positions;
+    let t_alpha_queried = &proof.t_alpha_queried;
+    let num_evaluations = gamma_proof.num_evaluations;
+    // a path holds the leaf, its sibling and one node per level above them
+    let path_len = num_evaluations.trailing_zeros() as usize + 1;
+    if !num_evaluations.is_power_of_two()
+        || num_evaluations != proof.num_evaluations
+        || t_alpha_queried.queried_evals.len() != positions.len()
+        || t_alpha_queried.queried_proofs.len() != positions.len()
+    {
+        return Err(LincheckVerifierError::TAlphaQueriesMismatch);
+    }
+    for (i, &position) in positions.iter().enumerate() {
+        let path = &t_alpha_queried.queried_proofs[i];
+        if path.len() != path_len
+            || path[0] != H::hash_elements(&[t_alpha_queried.queried_evals[i]])
+            || MerkleTree::<H>::verify(proof.t_alpha_commitment, position, path).is_err()
+        {
+            return Err(LincheckVerifierError::TAlphaOpeningErr(position));
+        }
+    }
 
+    let beta = E::from(proof.beta);
+    let gamma = E::from(proof.gamma);
+    let xs = get_queried_domain_elements::<B, E>(num_evaluations, B::ONE, positions);
+    for (i, x) in xs.into_iter().enumerate() {
+        let q_x = gamma_proof.unpadded_queried_evaluations[i];
+        if t_alpha_queried.queried_evals[i] - gamma != q_x * (x - beta) {
+            return Err(LincheckVerifierError::GammaMismatch(positions[i]));
+        }
+    }
     Ok(())
 }
@@ -1,71 +1,292 @@
 use crate::errors::RowcheckVerifierError;
 
-use fractal_indexer::snark_keys::VerifierKey;
-use fractal_proofs::{FieldElement, RowcheckProof, get_complementary_poly, polynom, TryInto};
+use fractal_indexer::{index::eval_domain_size, snark_keys::VerifierKey};
+use fractal_proofs::{FieldElement, ProofOptions, RowcheckProof, VanishingPolyEvaluator, get_complementary_poly, polynom, public_input_polys};
 
-use fractal_sumcheck::log::debug;
+use fractal_sumcheck::log::{debug, trace};
+use low_degree::low_degree_verifier::{get_queried_domain_elements, verify_low_degree_proof_ref};
 use winter_crypto::{ElementHasher, RandomCoin, MerkleTree};
 use winter_fri::{DefaultVerifierChannel, FriVerifier};
 use winter_math::StarkField;
 
+/// Verifies the rowcheck, i.e. that f_Az * f_Bz - f_Cz vanishes on H and that z starts with
+/// `public_inputs`.
 pub fn verify_rowcheck_proof<
     B: StarkField,
     E: FieldElement<BaseField = B>,
     H: ElementHasher<BaseField = B>,
 >(
     verifier_key: &VerifierKey<H, B>,
+    options: &ProofOptions,
     proof: RowcheckProof<B, E, H>,
-    // Change to include public seed
+    public_inputs: &[B],
 ) -> Result<(), RowcheckVerifierError> {
+    verify_rowcheck_proof_ref(verifier_key, options, &proof, public_inputs)
+}
 
+/// Same as [verify_rowcheck_proof], but borrows the proof. Only the FRI proof and layer
+/// commitments are cloned, for winterfell's verifier channel.
+///
+/// The prover commits to f_Az, f_Bz, f_Cz and f_z and to s, then proves w has low degree,
+/// which draws the queried positions. The verifier replays that, checks the openings, and
+/// checks s * v_H = f_Az * f_Bz - f_Cz at every queried point. FRI then shows s has low degree,
+/// so an assignment that doesn't satisfy the constraints leaves no polynomial s that passes.
+///
+/// `public_inputs` are the first values of z. At every queried point the verifier checks
+/// f_z = x_hat + v_X * w, where x_hat interpolates them and v_X vanishes where z should equal
+/// them, so z can only start with other values if w isn't a low degree polynomial. That f_z
+/// is the z that f_Az, f_Bz and f_Cz were computed from is left to the linchecks.
+pub fn verify_rowcheck_proof_ref<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    options: &ProofOptions,
+    proof: &RowcheckProof<B, E, H>,
+    public_inputs: &[B],
+) -> Result<(), RowcheckVerifierError> {
+    if proof.options != options.fri_options {
+        return Err(RowcheckVerifierError::OptionsMismatch);
+    }
+    let eval_domain_size = eval_domain_size(verifier_key.params.max_degree, options.fri_options.blowup_factor());
+    if proof.num_evaluations != eval_domain_size {
+        return Err(RowcheckVerifierError::EvaluationDomainMismatch {
+            expected: eval_domain_size,
+            actual: proof.num_evaluations,
+        });
+    }
     let mut public_coin = RandomCoin::new(&[]);
+    verify_query_positions(verifier_key, options, proof, public_inputs.len(), &mut public_coin)?;
 
     let mut channel = DefaultVerifierChannel::new(
-        proof.s_proof,
-        proof.s_commitments,
+        proof.s_proof.clone(),
+        proof.s_commitments.clone(),
         proof.num_evaluations,
-        proof.options.folding_factor(),
+        options.fri_options.folding_factor(),
     )?;
-    let s_queried_evals = proof.s_queried_evals;
-    let s_original_evals = proof.s_original_evals;
+    let s_queried_evals = &proof.s_queried_evals;
+    let s_original_evals = &proof.s_original_evals;
     
-    let s_original_proof = proof.s_original_proof;
-    MerkleTree::verify_batch(&proof.s_eval_root, &proof.queried_positions.clone(), &s_original_proof).map_err(|err| RowcheckVerifierError::MerkleTreeErr(err))?;
-    verify_lower_degree::<B, E, H>(4 * verifier_key.params.max_degree, verifier_key.params.num_input_variables - 1, verifier_key.params.max_degree, s_original_evals, s_queried_evals.clone(), proof.queried_positions.clone())?;
+    let s_original_proof = &proof.s_original_proof;
+    MerkleTree::verify_batch(&proof.s_eval_root, &proof.queried_positions, s_original_proof).map_err(|err| RowcheckVerifierError::MerkleTreeErr(err))?;
+    check_opened_leaves(&s_original_proof.leaves, &proof.queried_positions, |i| H::hash_elements(&[s_original_evals[i]]))?;
+    MerkleTree::verify_batch(&proof.f_eval_root, &proof.queried_positions, &proof.f_eval_proof)?;
+    check_opened_leaves(&proof.f_eval_proof.leaves, &proof.queried_positions, |i| {
+        H::hash_elements(&[proof.f_az_evals[i], proof.f_bz_evals[i], proof.f_cz_evals[i], proof.f_z_evals[i]])
+    })?;
+    verify_s_quotient(verifier_key, proof)?;
+    verify_public_inputs(verifier_key, proof, public_inputs)?;
+
+    verify_lower_degree::<B, E, H>(eval_domain_size, s_max_degree(verifier_key), verifier_key.params.max_degree, s_original_evals, s_queried_evals, &proof.queried_positions)?;
     
 
     let fri_verifier = FriVerifier::<B, E, DefaultVerifierChannel<E, H>, H>::new(
         &mut channel,
         &mut public_coin,
-        proof.options.clone(),
+        options.fri_options.clone(),
         verifier_key.params.max_degree - 1,
     )?;
     debug!("rowcheck max_poly_degree {}", verifier_key.params.max_degree - 1);
-    fri_verifier.verify(&mut channel, &s_queried_evals, &proof.queried_positions).map_err(|err| RowcheckVerifierError::FriVerifierErr(err))
+    fri_verifier.verify(&mut channel, s_queried_evals, &proof.queried_positions).map_err(|err| RowcheckVerifierError::FriVerifierErr(err))
+}
+
+// s = (f_Az * f_Bz - f_Cz) / v_H has degree at most |H| - 2.
+fn s_max_degree<B: StarkField, H: ElementHasher<BaseField = B>>(verifier_key: &VerifierKey<H, B>) -> usize {
+    std::cmp::max(verifier_key.params.num_input_variables, verifier_key.params.num_constraints) - 2
+}
+
+// w = (z - x_hat) / v_X, where z has degree below num_input_variables and v_X has degree
+// num_public_inputs.
+fn w_max_degree<B: StarkField, H: ElementHasher<BaseField = B>>(verifier_key: &VerifierKey<H, B>, num_public_inputs: usize) -> usize {
+    verifier_key.params.num_input_variables.saturating_sub(num_public_inputs + 1)
+}
+
+// Replays the start of the prover's transcript, see RowcheckProver::generate_proof. The low
+// degree proof for w re-derives the `options.num_queries` queried positions, which the
+// rowcheck shares.
+fn verify_query_positions<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    options: &ProofOptions,
+    proof: &RowcheckProof<B, E, H>,
+    num_public_inputs: usize,
+    public_coin: &mut RandomCoin<B, H>,
+) -> Result<(), RowcheckVerifierError> {
+    public_coin.reseed(proof.f_eval_root);
+    public_coin.reseed(proof.s_eval_root);
+    if !proof.num_evaluations.is_power_of_two() || proof.w_proof.num_evaluations != proof.num_evaluations {
+        return Err(RowcheckVerifierError::QueryPositionsMismatch);
+    }
+    verify_low_degree_proof_ref(&proof.w_proof, w_max_degree(verifier_key, num_public_inputs), options, public_coin)?;
+    if proof.w_proof.queried_positions != proof.queried_positions {
+        return Err(RowcheckVerifierError::QueryPositionsMismatch);
+    }
+    let num_queries = proof.queried_positions.len();
+    let num_values = [&proof.s_original_evals, &proof.s_queried_evals, &proof.f_az_evals, &proof.f_bz_evals, &proof.f_cz_evals, &proof.f_z_evals, &proof.w_proof.unpadded_queried_evaluations];
+    if num_values.iter().any(|values| values.len() != num_queries) {
+        return Err(RowcheckVerifierError::QueriedValuesLengthMismatch);
+    }
+    Ok(())
 }
 
+// The opened leaves come in the order of the queried positions.
+fn check_opened_leaves<D: PartialEq>(
+    leaves: &[D],
+    positions: &[usize],
+    expected_leaf: impl Fn(usize) -> D,
+) -> Result<(), RowcheckVerifierError> {
+    if leaves.len() != positions.len() {
+        return Err(RowcheckVerifierError::QueriedValuesLengthMismatch);
+    }
+    for (i, (leaf, &position)) in leaves.iter().zip(positions.iter()).enumerate() {
+        if *leaf != expected_leaf(i) {
+            return Err(RowcheckVerifierError::LeafMismatch(position));
+        }
+    }
+    Ok(())
+}
+
+// Checks s(x) * v_H(x) = f_Az(x) * f_Bz(x) - f_Cz(x) at every queried x.
+fn verify_s_quotient<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    proof: &RowcheckProof<B, E, H>,
+) -> Result<(), RowcheckVerifierError> {
+    match find_quotient_mismatches(verifier_key, proof, true).first() {
+        Some(&(position, _, _)) => Err(RowcheckVerifierError::QuotientMismatch(position)),
+        None => Ok(()),
+    }
+}
+
+/// Checks s(x) * v_H(x) = f_Az(x) * f_Bz(x) - f_Cz(x) at every queried position instead of
+/// stopping at the first mismatch, and returns each failing
+/// `(position, s * v_H, f_Az * f_Bz - f_Cz)`.
+pub fn verify_s_quotient_all<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    proof: &RowcheckProof<B, E, H>,
+) -> Result<(), Vec<(usize, E, E)>> {
+    let mismatches = find_quotient_mismatches(verifier_key, proof, false);
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    Err(mismatches)
+}
+
+// Returns (position, s * v_H, f_Az * f_Bz - f_Cz) wherever the two differ, stopping after the
+// first one if `stop_at_first` is set.
+fn find_quotient_mismatches<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(verifier_key: &VerifierKey<H, B>, proof: &RowcheckProof<B, E, H>, stop_at_first: bool) -> Vec<(usize, E, E)> {
+    let size_subgroup_h = std::cmp::max(verifier_key.params.num_input_variables, verifier_key.params.num_constraints);
+    let v_h = VanishingPolyEvaluator::new(E::from(verifier_key.params.eta), size_subgroup_h);
+    let xs = get_queried_domain_elements::<B, E>(proof.num_evaluations, B::ONE, &proof.queried_positions);
+    let mut mismatches = Vec::new();
+    for (i, x) in xs.into_iter().enumerate() {
+        let lhs = proof.s_original_evals[i] * v_h.eval(x);
+        let rhs = proof.f_az_evals[i] * proof.f_bz_evals[i] - proof.f_cz_evals[i];
+        if lhs != rhs {
+            mismatches.push((proof.queried_positions[i], lhs, rhs));
+            if stop_at_first {
+                break;
+            }
+        }
+    }
+    mismatches
+}
+
+// Checks f_z(x) = x_hat(x) + v_X(x) * w(x) at every queried x. w_proof has already checked its
+// unpadded evaluations against its tree root and its FRI proof.
+fn verify_public_inputs<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    proof: &RowcheckProof<B, E, H>,
+    public_inputs: &[B],
+) -> Result<(), RowcheckVerifierError> {
+    let (x_hat, v_x) = public_input_polys(public_inputs, verifier_key.params.eta, verifier_key.params.num_input_variables);
+    let xs = get_queried_domain_elements::<B, E>(proof.num_evaluations, B::ONE, &proof.queried_positions);
+    for (i, x) in xs.into_iter().enumerate() {
+        let w = proof.w_proof.unpadded_queried_evaluations[i];
+        if proof.f_z_evals[i] != polynom::eval(&x_hat, x) + polynom::eval(&v_x, x) * w {
+            return Err(RowcheckVerifierError::PublicInputsMismatch(proof.queried_positions[i]));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the padding of s at every queried position instead of stopping at the first
+/// mismatch, and returns each failing `(position, got, computed)`: the padded evaluation in
+/// the proof and the one computed from the unpadded evaluation.
+pub fn verify_s_padding_all<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    proof: &RowcheckProof<B, E, H>,
+) -> Result<(), Vec<(usize, E, E)>> {
+    let eval_domain_size = eval_domain_size(verifier_key.params.max_degree, proof.options.blowup_factor());
+    let mismatches = find_padding_mismatches::<B, E>(eval_domain_size, s_max_degree(verifier_key), verifier_key.params.max_degree, &proof.s_original_evals, &proof.s_queried_evals, &proof.queried_positions, false);
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    Err(mismatches)
+}
 
 fn verify_lower_degree<
     B: StarkField,
     E: FieldElement<BaseField = B>,
     H: ElementHasher<BaseField = B>,
 >(eval_domain_size: usize, original_degree: usize, max_degree: usize, 
-    original_evals: Vec<E>, final_evals: Vec<E>, positions: Vec<usize>) -> Result<(), RowcheckVerifierError> {
+    original_evals: &[E], final_evals: &[E], positions: &[usize]) -> Result<(), RowcheckVerifierError> {
+    let mismatches = find_padding_mismatches::<B, E>(eval_domain_size, original_degree, max_degree, original_evals, final_evals, positions, true);
+    if let Some((position, got, computed)) = mismatches.first() {
+        debug!(
+            "Position {}: final eval {:?}, computed from the original eval = {:?}",
+            position,
+            got,
+            computed
+        );
+        trace!("Original_evals = {:?}", original_evals);
+        return Err(RowcheckVerifierError::SmallPolyAdjustmentErr());
+    }
+    Ok(())
+}
+
+// Returns (position, final eval, original eval * complementary poly) wherever the two differ,
+// stopping after the first one if `stop_at_first` is set.
+fn find_padding_mismatches<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+>(eval_domain_size: usize, original_degree: usize, max_degree: usize,
+    original_evals: &[E], final_evals: &[E], positions: &[usize], stop_at_first: bool) -> Vec<(usize, E, E)> {
     let comp_poly = get_complementary_poly::<E>(original_degree, max_degree - 1);
-    let eval_domain_base = E::from(B::get_root_of_unity(eval_domain_size.trailing_zeros()));
-    let eval_domain_pows = positions.iter().map(|&x| {let z: u64 = x.try_into().unwrap(); z}).collect::<Vec<u64>>();
-    let eval_domain_elts = eval_domain_pows.iter().map(|&x| eval_domain_base.exp(E::PositiveInteger::from(x))).collect::<Vec<E>>();
+    let eval_domain_elts = get_queried_domain_elements::<B, E>(eval_domain_size, B::ONE, positions);
     let eval_domain_evals = polynom::eval_many(&comp_poly, &eval_domain_elts);
-    for (pos, _) in eval_domain_elts.iter().enumerate() {
-        if original_evals[pos].mul(eval_domain_evals[pos]) != final_evals[pos] {
-            println!("Position {}", pos);
-            println!("Original_evals = {:?}", original_evals);
-            println!("Domain elt = {:?}", eval_domain_elts[pos]);
-            println!("Mul = {:?}", original_evals[pos].mul(eval_domain_evals[pos]));
-            println!("Final evals = {:?}", final_evals[pos]);
-            return Err(RowcheckVerifierError::SmallPolyAdjustmentErr());
+    let mut mismatches = Vec::new();
+    for (i, &position) in positions.iter().enumerate() {
+        let computed = original_evals[i].mul(eval_domain_evals[i]);
+        if computed != final_evals[i] {
+            mismatches.push((position, final_evals[i], computed));
+            if stop_at_first {
+                break;
+            }
         }
     }
-    Ok(())
+    mismatches
 }
-
@@ -1 +1,933 @@
+use crate::errors::{FractalVerifierError, LincheckVerifierError, RowcheckVerifierError};
+use crate::{
+    verify_lincheck_proof_ref, verify_rowcheck_proof_ref, verify_s_padding_all,
+    verify_s_quotient_all,
+};
+use crate::verifier::*;
 
+use fractal_indexer::{
+    index::{build_index_domains_with_blowup, Index, IndexParams},
+    indexed_matrix::index_matrix,
+    snark_keys::*,
+};
+use fractal_proofs::{
+    ByteReader, Deserializable, FieldTag, FractalProof, FriOptions, ProofOptions, Serializable,
+    SliceReader,
+};
+use fractal_prover::{
+    prover::{prove, FractalProver},
+    FractalOptions,
+};
+use fractal_sumcheck::errors::{SumcheckPolynomial, SumcheckVerifierError};
+use fractal_utils::channel_utils::public_inputs_to_bytes;
+use fractal_utils::DEFAULT_PAR_THRESHOLD;
+use low_degree::errors::LowDegreeVerifierError;
+use models::jsnark_arith_parser::JsnarkArithReaderParser;
+use models::jsnark_wire_parser::JsnarkWireReaderParser;
+use models::r1cs::{Matrix, R1csAssignment};
+
+use winter_crypto::hashers::Rp64_256;
+use winter_crypto::Hasher;
+use winter_math::fields::f64::BaseElement;
+use winter_math::fields::QuadExtension;
+use winter_math::FieldElement;
+use winter_rand_utils::prng_vector;
+
+type H = Rp64_256;
+
+#[test]
+fn test_verify_with_key_commitment() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let key_commitment = verifier_key.commitment();
+    let pub_inputs_bytes = vec![0u8];
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert!(verify_fractal_proof_with_key_commitment(
+        key_commitment,
+        verifier_key,
+        &proof_options,
+        proof,
+        pub_inputs_bytes
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_verify_with_wrong_key_commitment() {
+    let (_, mut verifier_key, _, _) = get_example_setup();
+    let key_commitment = verifier_key.commitment();
+    assert!(verify_key_commitment(key_commitment, &verifier_key).is_ok());
+
+    let wrong_commitment = H::hash(&[1u8]);
+    assert_eq!(
+        verify_key_commitment(wrong_commitment, &verifier_key),
+        Err(FractalVerifierError::VerifierKeyCommitmentErr)
+    );
+
+    verifier_key.params.num_non_zero *= 2;
+    assert_eq!(
+        verify_key_commitment(key_commitment, &verifier_key),
+        Err(FractalVerifierError::VerifierKeyCommitmentErr)
+    );
+}
+
+#[test]
+fn test_reject_modified_gamma() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    proof.lincheck_b.gamma = proof.lincheck_b.gamma + BaseElement::ONE;
+    assert!(verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_err());
+}
+
+#[test]
+fn test_reject_modified_alpha() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    proof.lincheck_a.alpha = proof.lincheck_a.alpha + BaseElement::ONE;
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::LincheckVerifierErr(
+            LincheckVerifierError::AlphaMismatch
+        ))
+    );
+}
+
+#[test]
+fn test_reject_modified_beta() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    proof.lincheck_c.beta = proof.lincheck_c.beta + BaseElement::ONE;
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::LincheckVerifierErr(
+            LincheckVerifierError::BetaMismatch
+        ))
+    );
+}
+
+#[test]
+fn test_reject_modified_matrix_openings() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    let alpha = proof.lincheck_a.alpha;
+    let position = proof.lincheck_a.matrix_sumcheck_proof.e_proof.queried_positions[0];
+    // lincheck a's row, col and val don't open against B's index
+    assert_eq!(
+        verify_lincheck_proof_ref(&verifier_key, &proof_options, &verifier_key.matrix_b_commitments, &proof.lincheck_a, alpha),
+        Err(LincheckVerifierError::MatrixOpeningErr(position))
+    );
+
+    proof.lincheck_a.val_queried.queried_evals[0] += BaseElement::ONE;
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::LincheckVerifierErr(
+            LincheckVerifierError::MatrixOpeningErr(position)
+        ))
+    );
+}
+
+#[test]
+fn test_lincheck_beta_over_extension_field() {
+    type Quad = QuadExtension<BaseElement>;
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut prover =
+        FractalProver::<BaseElement, Quad, H>::new(prover_key, options, vec![], wires, pub_inputs_bytes.clone());
+    let mut proof = prover.generate_proof().unwrap();
+    let alpha = proof.lincheck_a.alpha;
+    let linchecks = [
+        (&verifier_key.matrix_a_commitments, &proof.lincheck_a),
+        (&verifier_key.matrix_b_commitments, &proof.lincheck_b),
+        (&verifier_key.matrix_c_commitments, &proof.lincheck_c),
+    ];
+    for (matrix_commitments, lincheck) in linchecks {
+        assert!(verify_lincheck_proof_ref(&verifier_key, &proof_options, matrix_commitments, lincheck, alpha).is_ok());
+    }
+    proof.lincheck_b.beta = proof.lincheck_b.beta + BaseElement::ONE;
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::LincheckVerifierErr(
+            LincheckVerifierError::BetaMismatch
+        ))
+    );
+}
+
+#[test]
+fn test_verify_borrowed_proof_twice() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    let alpha = proof.lincheck_a.alpha;
+    for _ in 0..2 {
+        assert!(verify_lincheck_proof_ref(&verifier_key, &proof_options, &verifier_key.matrix_a_commitments, &proof.lincheck_a, alpha).is_ok());
+    }
+    assert!(verify_rowcheck_proof_ref(&verifier_key, &proof_options, &proof.rowcheck_proof, &[]).is_ok());
+    assert!(verify_fractal_proof_ref(&verifier_key, &proof_options, &proof, &pub_inputs_bytes).is_ok());
+    // the proof is still ours afterwards
+    assert!(verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_ok());
+}
+
+#[test]
+fn test_report_all_s_padding_mismatches() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert_eq!(verify_s_padding_all(&verifier_key, &proof.rowcheck_proof), Ok(()));
+
+    let rowcheck = &mut proof.rowcheck_proof;
+    let last = rowcheck.queried_positions.len() - 1;
+    let honest = [rowcheck.s_queried_evals[1], rowcheck.s_queried_evals[last]];
+    rowcheck.s_queried_evals[1] += BaseElement::ONE;
+    rowcheck.s_queried_evals[last] += BaseElement::ONE;
+
+    let mismatches = verify_s_padding_all(&verifier_key, &proof.rowcheck_proof).unwrap_err();
+    let rowcheck = &proof.rowcheck_proof;
+    assert_eq!(
+        mismatches,
+        vec![
+            (rowcheck.queried_positions[1], rowcheck.s_queried_evals[1], honest[0]),
+            (rowcheck.queried_positions[last], rowcheck.s_queried_evals[last], honest[1]),
+        ]
+    );
+    // the default still stops at the first one
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::RowcheckVerifierErr(
+            RowcheckVerifierError::SmallPolyAdjustmentErr()
+        ))
+    );
+}
+
+#[test]
+fn test_report_all_s_quotient_mismatches() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let mut proof = get_example_proof(prover_key, options, wires, vec![0u8]);
+    assert_eq!(verify_s_quotient_all(&verifier_key, &proof.rowcheck_proof), Ok(()));
+
+    let rowcheck = &mut proof.rowcheck_proof;
+    let last = rowcheck.queried_positions.len() - 1;
+    rowcheck.f_cz_evals[1] += BaseElement::ONE;
+    rowcheck.f_cz_evals[last] += BaseElement::ONE;
+
+    let mismatches = verify_s_quotient_all(&verifier_key, &proof.rowcheck_proof).unwrap_err();
+    let rowcheck = &proof.rowcheck_proof;
+    assert_eq!(
+        mismatches.iter().map(|&(position, _, _)| position).collect::<Vec<_>>(),
+        vec![rowcheck.queried_positions[1], rowcheck.queried_positions[last]]
+    );
+    // f_Cz is one too large, so f_Az * f_Bz - f_Cz is one short of s * v_H
+    for &(_, lhs, rhs) in &mismatches {
+        assert_eq!(lhs - rhs, BaseElement::ONE);
+    }
+}
+
+#[test]
+fn test_sumcheck_errors_carry_context() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    let e_proof = &mut proof.lincheck_a.products_sumcheck_proof.e_proof;
+    e_proof.unpadded_queried_evaluations[2] += BaseElement::ONE;
+    let position = e_proof.queried_positions[2];
+    assert_eq!(
+        verify_fractal_proof_ref(&verifier_key, &proof_options, &proof, &pub_inputs_bytes),
+        Err(FractalVerifierError::LincheckVerifierErr(
+            LincheckVerifierError::UnsoundProduct(SumcheckVerifierError::PolynomialErr(
+                SumcheckPolynomial::E,
+                LowDegreeVerifierError::TreeLeafMismatch(position)
+            ))
+        ))
+    );
+
+    let (prover_key, _, options, wires) = get_example_setup();
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    let g_proof = &mut proof.lincheck_b.matrix_sumcheck_proof.g_proof;
+    g_proof.unpadded_queried_evaluations[0] += BaseElement::ONE;
+    let position = g_proof.queried_positions[0];
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::LincheckVerifierErr(
+            LincheckVerifierError::UnsoundMatrix(SumcheckVerifierError::PolynomialErr(
+                SumcheckPolynomial::G,
+                LowDegreeVerifierError::TreeLeafMismatch(position)
+            ))
+        ))
+    );
+}
+
+#[test]
+fn test_validate_proof_structure() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let num_queries = options.num_queries;
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert!(validate_proof_structure(&proof, &proof_options).is_ok());
+    assert!(validate_proof_structure(&proof, &ProofOptions::new(proof_options.fri_options.clone(), num_queries - 1)).is_err());
+
+    let eval = proof.rowcheck_proof.s_queried_evals.pop().unwrap();
+    assert!(validate_proof_structure(&proof, &proof_options).is_err());
+    proof.rowcheck_proof.s_queried_evals.push(eval);
+
+    let path = proof.lincheck_b.col_queried.queried_proofs.pop().unwrap();
+    assert!(validate_proof_structure(&proof, &proof_options).is_err());
+    proof.lincheck_b.col_queried.queried_proofs.push(path);
+
+    let position = proof.lincheck_a.products_sumcheck_proof.queried_positions.pop().unwrap();
+    assert!(validate_proof_structure(&proof, &proof_options).is_err());
+    proof.lincheck_a.products_sumcheck_proof.queried_positions.push(position);
+
+    let g_proof = &mut proof.lincheck_a.products_sumcheck_proof.g_proof;
+    let eval = g_proof.padded_queried_evaluations.pop().unwrap();
+    assert!(validate_proof_structure(&proof, &proof_options).is_err());
+    proof.lincheck_a.products_sumcheck_proof.g_proof.padded_queried_evaluations.push(eval);
+
+    let commitment = proof.lincheck_c.matrix_sumcheck_proof.e_proof.commitments.pop().unwrap();
+    assert!(validate_proof_structure(&proof, &proof_options).is_err());
+    proof.lincheck_c.matrix_sumcheck_proof.e_proof.commitments.push(commitment);
+
+    assert!(validate_proof_structure(&proof, &proof_options).is_ok());
+    proof.lincheck_c.val_queried.queried_evals.pop();
+    assert!(matches!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::MalformedProof(_))
+    ));
+}
+
+#[test]
+fn test_proof_serialization_round_trip() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    let bytes = proof.to_bytes();
+
+    let mut reader = SliceReader::new(&bytes);
+    let read_proof = FractalProof::<BaseElement, BaseElement, H>::read_from(&mut reader).unwrap();
+    assert!(!reader.has_more_bytes());
+    assert_eq!(read_proof.to_bytes(), bytes);
+    assert!(verify_fractal_proof(verifier_key, &proof_options, read_proof, pub_inputs_bytes).is_ok());
+
+    let truncated = &bytes[..bytes.len() - 1];
+    assert!(
+        FractalProof::<BaseElement, BaseElement, H>::read_from(&mut SliceReader::new(truncated))
+            .is_err()
+    );
+}
+
+fn to_hex(value: &BaseElement) -> String {
+    value.to_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn test_proof_json_round_trip() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    let json = proof.to_json();
+
+    let read_proof = FractalProof::<BaseElement, BaseElement, H>::from_json(&json).unwrap();
+    assert_eq!(read_proof.to_bytes(), proof.to_bytes());
+    assert_eq!(read_proof.to_json(), json);
+    assert!(verify_fractal_proof(verifier_key, &proof_options, read_proof, pub_inputs_bytes).is_ok());
+
+    // fields are written one by one, with only elements and digests as hex
+    assert!(json.contains("\"rowcheck_proof\":{\"options\":{\"blowup_factor\":"));
+    assert!(json.contains("\"queried_positions\":["));
+    assert!(json.contains(&format!("\"alpha\":\"{}\"", to_hex(&proof.lincheck_a.alpha))));
+
+    // a digest with trailing bytes wasn't written by to_json
+    let padded = json.replacen("\",\"rowcheck_proof\"", "00\",\"rowcheck_proof\"", 1);
+    assert_ne!(padded, json);
+    assert!(FractalProof::<BaseElement, BaseElement, H>::from_json(&padded).is_err());
+}
+
+#[test]
+fn test_reject_proof_over_different_field() {
+    type Quad = QuadExtension<BaseElement>;
+    let pub_inputs_bytes = vec![0u8];
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let base_proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    let (prover_key, _, options, wires) = get_example_setup();
+    let mut prover =
+        FractalProver::<BaseElement, Quad, H>::new(prover_key, options, vec![], wires, pub_inputs_bytes.clone());
+    let quad_proof = prover.generate_proof().unwrap();
+
+    let base_bytes = base_proof.to_bytes();
+    let quad_bytes = quad_proof.to_bytes();
+    assert!(verify_fractal_proof_bytes::<BaseElement, BaseElement, H>(&verifier_key, &proof_options, &base_bytes, &pub_inputs_bytes).is_ok());
+    assert!(verify_fractal_proof_bytes::<BaseElement, Quad, H>(&verifier_key, &proof_options, &quad_bytes, &pub_inputs_bytes).is_ok());
+    assert_eq!(
+        verify_fractal_proof_bytes::<BaseElement, Quad, H>(&verifier_key, &proof_options, &base_bytes, &pub_inputs_bytes),
+        Err(FractalVerifierError::FieldMismatch)
+    );
+    assert_eq!(
+        verify_fractal_proof_bytes::<BaseElement, BaseElement, H>(&verifier_key, &proof_options, &quad_bytes, &pub_inputs_bytes),
+        Err(FractalVerifierError::FieldMismatch)
+    );
+
+    let mut tagged = base_proof;
+    tagged.field_tag = FieldTag::of::<BaseElement, Quad>();
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, tagged, pub_inputs_bytes),
+        Err(FractalVerifierError::FieldMismatch)
+    );
+}
+
+#[test]
+fn test_verify_with_logging_disabled() {
+    log::set_max_level(log::LevelFilter::Off);
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert!(verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_ok());
+}
+
+#[test]
+fn test_reject_wrong_public_inputs() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let proof = get_example_proof(prover_key, options, wires, vec![0u8]);
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, vec![1u8]),
+        Err(FractalVerifierError::PublicInputMismatch)
+    );
+}
+
+#[test]
+fn test_reject_swapped_gammas() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert_ne!(proof.lincheck_a.gamma, proof.lincheck_b.gamma);
+    std::mem::swap(&mut proof.lincheck_a.gamma, &mut proof.lincheck_b.gamma);
+    assert!(matches!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::LincheckVerifierErr(
+            LincheckVerifierError::UnsoundGamma(LowDegreeVerifierError::QueryPositionsMismatch)
+        ))
+    ));
+}
+
+#[test]
+fn test_reject_modified_t_alpha_opening() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+    let mut proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    let position = proof.lincheck_c.gamma_proof.queried_positions[0];
+    proof.lincheck_c.t_alpha_queried.queried_evals[0] += BaseElement::ONE;
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::LincheckVerifierErr(
+            LincheckVerifierError::TAlphaOpeningErr(position)
+        ))
+    );
+}
+
+#[test]
+fn test_verify_with_different_fri_remainders() {
+    for max_remainder_size in [16, 64] {
+        let (prover_key, verifier_key, mut options, wires) = get_example_setup();
+        options.fri_options = FractalOptions::<BaseElement>::get_fri_options(
+            4,
+            4,
+            max_remainder_size,
+            options.evaluation_domain.len(),
+        )
+        .unwrap();
+        let proof_options = options.proof_options();
+        let pub_inputs_bytes = vec![0u8];
+        let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+        assert!(verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_ok());
+    }
+}
+
+#[test]
+fn test_reject_fri_remainder_larger_than_domain() {
+    let (_, _, options, _) = get_example_setup();
+    let domain_len = options.evaluation_domain.len();
+    assert!(FractalOptions::<BaseElement>::get_fri_options(4, 4, domain_len, domain_len).is_err());
+    assert!(FractalOptions::<BaseElement>::get_fri_options(4, 4, 4, domain_len).is_err());
+}
+
+#[test]
+fn test_verify_with_blowup_8() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup_with_blowup(8);
+    let proof_options = options.proof_options();
+    assert_eq!(
+        options.evaluation_domain.len(),
+        8 * verifier_key.params.max_degree
+    );
+    let pub_inputs_bytes = vec![0u8];
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert!(verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_ok());
+}
+
+#[test]
+fn test_reject_proof_with_prover_chosen_options() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = vec![0u8];
+
+    // a proof opened at a single position, which the proof alone doesn't tell apart
+    let mut one_query = options.clone();
+    one_query.num_queries = 1;
+    let proof = get_example_proof(prover_key, one_query, wires, pub_inputs_bytes.clone());
+    let one_query_options = ProofOptions::new(proof_options.fri_options.clone(), 1);
+    assert!(verify_fractal_proof_ref(&verifier_key, &one_query_options, &proof, &pub_inputs_bytes).is_ok());
+    assert!(matches!(
+        verify_fractal_proof_ref(&verifier_key, &proof_options, &proof, &pub_inputs_bytes),
+        Err(FractalVerifierError::MalformedProof(_))
+    ));
+    assert!(matches!(
+        verify_rowcheck_proof_ref(&verifier_key, &proof_options, &proof.rowcheck_proof, &[]),
+        Err(RowcheckVerifierError::LowDegreeVerifierErr(LowDegreeVerifierError::QueryPositionsMismatch))
+    ));
+
+    // a proof with a larger blowup than the verifier's, over a domain to match
+    let (prover_key, _, options, wires) = get_example_setup_with_blowup(8);
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert_eq!(
+        verify_fractal_proof_ref(&verifier_key, &proof_options, &proof, &pub_inputs_bytes),
+        Err(FractalVerifierError::OptionsMismatch("rowcheck".to_string()))
+    );
+    assert_eq!(
+        verify_rowcheck_proof_ref(&verifier_key, &proof_options, &proof.rowcheck_proof, &[]),
+        Err(RowcheckVerifierError::OptionsMismatch)
+    );
+    let alpha = proof.lincheck_a.alpha;
+    assert_eq!(
+        verify_lincheck_proof_ref(&verifier_key, &proof_options, &verifier_key.matrix_a_commitments, &proof.lincheck_a, alpha),
+        Err(LincheckVerifierError::OptionsMismatch)
+    );
+    // the domain size is checked against the verifier's blowup, not the proof's
+    assert!(matches!(
+        check_circuit_size(&verifier_key, &proof_options, &proof),
+        Err(FractalVerifierError::CircuitSizeMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_reject_rowcheck_over_different_domain() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    let mut proof = get_example_proof(prover_key, options, wires, vec![0u8]);
+    let expected = proof.rowcheck_proof.num_evaluations;
+    proof.rowcheck_proof.num_evaluations *= 2;
+    assert_eq!(
+        verify_rowcheck_proof_ref(&verifier_key, &proof_options, &proof.rowcheck_proof, &[]),
+        Err(RowcheckVerifierError::EvaluationDomainMismatch { expected, actual: 2 * expected })
+    );
+}
+
+#[test]
+fn test_index_params_from_jsnark() {
+    let mut arith_parser = JsnarkArithReaderParser::<BaseElement>::new().unwrap();
+    arith_parser.parse_arith_file("../fractal_examples/jsnark_outputs/sample.arith", false);
+    let r1cs = arith_parser.clone_r1cs();
+    let params = IndexParams::<BaseElement>::from_r1cs(&r1cs);
+    assert!(params.num_input_variables >= r1cs.num_cols());
+    assert!(params.num_constraints >= r1cs.num_rows());
+    assert!(params.num_non_zero >= r1cs.max_num_nonzero());
+    assert!(params.max_degree.is_power_of_two());
+
+    // get_example_setup builds its keys from these params
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    assert_eq!(verifier_key.params.max_degree, params.max_degree);
+    let pub_inputs_bytes = vec![0u8];
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert!(verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_ok());
+}
+
+#[test]
+fn test_prove_and_verify_small_circuit() {
+    let (a, b, c, z) = get_small_circuit();
+    let pub_inputs_bytes = vec![0u8];
+    let (proof, verifier_key, proof_options) =
+        prove::<BaseElement, BaseElement, H>(a, b, c, z, pub_inputs_bytes.clone(), 32).unwrap();
+    assert!(verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_ok());
+}
+
+#[test]
+fn test_prove_and_verify_in_one_call() {
+    let (a, b, c, z) = get_small_circuit();
+    let (proof, verifier_key, proof_options) =
+        prove::<BaseElement, BaseElement, H>(a.clone(), b.clone(), c.clone(), z.clone(), vec![0u8], 32)
+            .unwrap();
+    assert!(verify(proof, verifier_key, &proof_options, vec![0u8]).is_ok());
+
+    let (proof, verifier_key, proof_options) =
+        prove::<BaseElement, BaseElement, H>(a, b, c, z, vec![0u8], 32).unwrap();
+    assert_eq!(
+        verify(proof, verifier_key, &proof_options, vec![1u8]),
+        Err(FractalVerifierError::PublicInputMismatch)
+    );
+
+    // prove pads a 3x3 instance to a power of two side
+    let identity: Vec<Vec<BaseElement>> = (0..3)
+        .map(|i| (0..3).map(|j| if i == j { BaseElement::ONE } else { BaseElement::ZERO }).collect())
+        .collect();
+    let a = Matrix::new("a", identity.clone()).unwrap();
+    let b = Matrix::new("b", identity.clone()).unwrap();
+    let c = Matrix::new("c", identity).unwrap();
+    let (proof, verifier_key, proof_options) =
+        prove::<BaseElement, BaseElement, H>(a, b, c, vec![BaseElement::ONE; 3], vec![0u8], 32)
+            .unwrap();
+    assert!(verify(proof, verifier_key, &proof_options, vec![0u8]).is_ok());
+}
+
+#[test]
+fn test_prove_with_constant_wire() {
+    // wires (1, x, w, y, 0, 0, 0, 0) with x * 1 = w and (x + 1) * 1 = y
+    let size = 8;
+    let row = |cols: &[usize]| {
+        let mut row = vec![BaseElement::ZERO; size];
+        for &col in cols {
+            row[col] = BaseElement::ONE;
+        }
+        row
+    };
+    let zero_rows = |n: usize| vec![row(&[]); n];
+    let a = [vec![row(&[1]), row(&[0, 1])], zero_rows(size - 2)].concat();
+    let b = [vec![row(&[0]), row(&[0])], zero_rows(size - 2)].concat();
+    let c = [vec![row(&[2]), row(&[3])], zero_rows(size - 2)].concat();
+    let a = Matrix::new("a", a).unwrap();
+    let b = Matrix::new("b", b).unwrap();
+    let c = Matrix::new("c", c).unwrap();
+
+    let x = BaseElement::new(3);
+    let mut assignment = R1csAssignment::new(1, size - 2);
+    assignment.set_input(0, x).unwrap();
+    assignment.set_witness(0, x).unwrap();
+    assignment.set_witness(1, x + BaseElement::ONE).unwrap();
+    let z = assignment.to_vec();
+    let (proof, verifier_key, proof_options) = prove::<BaseElement, BaseElement, H>(
+        a.clone(),
+        b.clone(),
+        c.clone(),
+        z.clone(),
+        vec![0u8],
+        32,
+    )
+    .unwrap();
+    assert!(verify(proof, verifier_key, &proof_options, vec![0u8]).is_ok());
+
+    // the same values with the constant moved to the last wire
+    let mut misplaced = z[1..].to_vec();
+    misplaced.push(z[0]);
+    let (proof, verifier_key, proof_options) =
+        prove::<BaseElement, BaseElement, H>(a, b, c, misplaced, vec![0u8], 32).unwrap();
+    assert!(verify(proof, verifier_key, &proof_options, vec![0u8]).is_err());
+}
+
+#[test]
+fn test_reject_proof_for_different_circuit_size() {
+    let (a, b, c, z) = get_small_circuit();
+    let pub_inputs_bytes = vec![0u8];
+    let (small_proof, small_verifier_key, small_proof_options) =
+        prove::<BaseElement, BaseElement, H>(a, b, c, z, pub_inputs_bytes.clone(), 32).unwrap();
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    assert_ne!(verifier_key.params.max_degree, small_verifier_key.params.max_degree);
+
+    assert!(matches!(
+        verify_fractal_proof(verifier_key, &proof_options, small_proof, pub_inputs_bytes.clone()),
+        Err(FractalVerifierError::CircuitSizeMismatch { .. })
+    ));
+    let proof = get_example_proof(prover_key, options, wires, pub_inputs_bytes.clone());
+    assert!(matches!(
+        verify_fractal_proof(small_verifier_key, &small_proof_options, proof, pub_inputs_bytes),
+        Err(FractalVerifierError::CircuitSizeMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_prove_with_cloned_prover_keys_on_threads() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup();
+    let proof_options = options.proof_options();
+    assert_eq!(
+        prover_key.clone().get_verifier_key().commitment(),
+        verifier_key.commitment()
+    );
+
+    let handles: Vec<_> = (0..2u8)
+        .map(|i| {
+            let prover_key = prover_key.clone();
+            let options = options.clone();
+            let wires = wires.clone();
+            std::thread::spawn(move || get_example_proof(prover_key, options, wires, vec![i]))
+        })
+        .collect();
+    for (i, handle) in handles.into_iter().enumerate() {
+        let proof = handle.join().unwrap();
+        let verifier_key = prover_key.get_verifier_key();
+        assert!(verify_fractal_proof(verifier_key, &proof_options, proof, vec![i as u8]).is_ok());
+    }
+}
+
+#[test]
+fn test_public_inputs_bound_to_assignment() {
+    let (prover_key, verifier_key, options, wires) = get_example_setup_with(4, 2);
+    let proof_options = options.proof_options();
+    let pub_inputs_bytes = public_inputs_to_bytes(&wires[..2]);
+    let proof = get_example_proof(
+        prover_key.clone(),
+        options.clone(),
+        wires.clone(),
+        pub_inputs_bytes.clone(),
+    );
+    assert!(verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_ok());
+
+    // the prover doesn't check the public inputs, so this proof claims a prefix z doesn't have
+    let mut forged_inputs = wires[..2].to_vec();
+    forged_inputs[1] += BaseElement::ONE;
+    let forged_bytes = public_inputs_to_bytes(&forged_inputs);
+    let proof = get_example_proof(
+        prover_key.clone(),
+        options.clone(),
+        wires.clone(),
+        forged_bytes.clone(),
+    );
+    assert!(matches!(
+        verify_fractal_proof(prover_key.get_verifier_key(), &proof_options, proof, forged_bytes),
+        Err(FractalVerifierError::RowcheckVerifierErr(
+            RowcheckVerifierError::PublicInputsMismatch(_)
+        ))
+    ));
+
+    let verifier_key = prover_key.get_verifier_key();
+    let proof = get_example_proof(
+        prover_key,
+        options,
+        wires.clone(),
+        public_inputs_to_bytes(&wires[..2]),
+    );
+    assert_eq!(
+        verify_fractal_proof(verifier_key, &proof_options, proof, public_inputs_to_bytes(&wires[..1])),
+        Err(FractalVerifierError::PublicInputsLengthMismatch {
+            expected: 16,
+            actual: 8
+        })
+    );
+}
+
+#[test]
+fn test_prove_and_verify_random_r1cs() {
+    for (seed, size) in [(1u8, 8), (2, 16), (3, 32)] {
+        let (a, b, c, z) = get_random_r1cs(seed, size);
+        let pub_inputs_bytes = vec![0u8];
+        let (proof, verifier_key, proof_options) =
+            prove::<BaseElement, BaseElement, H>(a, b, c, z, pub_inputs_bytes.clone(), 32).unwrap();
+        assert!(
+            verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes).is_ok(),
+            "random R1CS of size {} with seed {} was rejected",
+            size,
+            seed
+        );
+    }
+}
+
+#[test]
+fn test_reject_random_r1cs_with_perturbed_witness() {
+    for (seed, size) in [(1u8, 8), (2, 16)] {
+        let (a, b, c, mut z) = get_random_r1cs(seed, size);
+        z[size - 1] += BaseElement::ONE;
+        let pub_inputs_bytes = vec![0u8];
+        // the prover doesn't check the assignment, so this is a proof for an unsatisfied one
+        let (proof, verifier_key, proof_options) =
+            prove::<BaseElement, BaseElement, H>(a, b, c, z, pub_inputs_bytes.clone(), 32).unwrap();
+        assert!(
+            matches!(
+                verify_fractal_proof(verifier_key, &proof_options, proof, pub_inputs_bytes),
+                Err(FractalVerifierError::RowcheckVerifierErr(
+                    RowcheckVerifierError::QuotientMismatch(_)
+                ))
+            ),
+            "perturbed witness for random R1CS of size {} with seed {} was accepted",
+            size,
+            seed
+        );
+    }
+}
+
+/// ***************  HELPERS *************** \\\
+
+// Same setup as the fractal-orchestrator example, run on the bundled jsnark sample.
+fn get_example_setup() -> (
+    ProverKey<H, BaseElement>,
+    VerifierKey<H, BaseElement>,
+    FractalOptions<BaseElement>,
+    Vec<BaseElement>,
+) {
+    get_example_setup_with_blowup(4)
+}
+
+fn get_example_setup_with_blowup(
+    lde_blowup: usize,
+) -> (
+    ProverKey<H, BaseElement>,
+    VerifierKey<H, BaseElement>,
+    FractalOptions<BaseElement>,
+    Vec<BaseElement>,
+) {
+    get_example_setup_with(lde_blowup, 0)
+}
+
+fn get_example_setup_with(
+    lde_blowup: usize,
+    num_public_inputs: usize,
+) -> (
+    ProverKey<H, BaseElement>,
+    VerifierKey<H, BaseElement>,
+    FractalOptions<BaseElement>,
+    Vec<BaseElement>,
+) {
+    let mut arith_parser = JsnarkArithReaderParser::<BaseElement>::new().unwrap();
+    arith_parser.parse_arith_file("../fractal_examples/jsnark_outputs/sample.arith", false);
+    let r1cs = arith_parser.clone_r1cs();
+
+    let mut wires_parser = JsnarkWireReaderParser::<BaseElement>::new().unwrap();
+    wires_parser.parse_wire_file("../fractal_examples/jsnark_outputs/sample.wires", false);
+    let wires = wires_parser.wires;
+
+    let mut index_params = IndexParams::<BaseElement>::from_r1cs(&r1cs);
+    index_params.num_public_inputs = num_public_inputs;
+    let eta = index_params.eta;
+    let eta_k = index_params.eta_k;
+
+    let index_domains =
+        build_index_domains_with_blowup::<BaseElement>(index_params.clone(), lde_blowup);
+    let indexed_a = index_matrix::<BaseElement>(&r1cs.A, &index_domains);
+    let indexed_b = index_matrix::<BaseElement>(&r1cs.B, &index_domains);
+    let indexed_c = index_matrix::<BaseElement>(&r1cs.C, &index_domains);
+    let index = Index::new(index_params, indexed_a, indexed_b, indexed_c);
+    let (prover_key, verifier_key) =
+        generate_prover_and_verifier_keys::<H, BaseElement, 1>(index).unwrap();
+
+    let evaluation_domain =
+        winter_math::get_power_series(index_domains.l_field_base, index_domains.l_field_len);
+    let options = FractalOptions::<BaseElement> {
+        degree_fs: r1cs.num_cols(),
+        size_subgroup_h: index_domains.h_field.len().next_power_of_two(),
+        size_subgroup_k: index_domains.k_field.len().next_power_of_two(),
+        summing_domain: index_domains.k_field,
+        evaluation_domain,
+        h_domain: index_domains.h_field,
+        eta,
+        eta_k,
+        fri_options: FriOptions::new(lde_blowup, 4, 32),
+        num_queries: 16,
+        par_threshold: DEFAULT_PAR_THRESHOLD,
+    };
+    (prover_key, verifier_key, options, wires)
+}
+
+// z = (1, 2, 3, z_1 * z_2, z_2 * z_3, ...): every row checks z_i * z_{i+1} = z_{i+2}.
+fn get_small_circuit() -> (
+    Matrix<BaseElement>,
+    Matrix<BaseElement>,
+    Matrix<BaseElement>,
+    Vec<BaseElement>,
+) {
+    let size = 8;
+    let mut z = vec![BaseElement::ONE, BaseElement::new(2), BaseElement::new(3)];
+    for i in 1..size - 2 {
+        z.push(z[i] * z[i + 1]);
+    }
+    let unit_row = |col: usize| {
+        let mut row = vec![BaseElement::ZERO; size];
+        row[col] = BaseElement::ONE;
+        row
+    };
+    let mut a_rows = vec![vec![BaseElement::ZERO; size]; size];
+    let mut b_rows = a_rows.clone();
+    let mut c_rows = a_rows.clone();
+    for i in 1..size - 2 {
+        a_rows[i - 1] = unit_row(i);
+        b_rows[i - 1] = unit_row(i + 1);
+        c_rows[i - 1] = unit_row(i + 2);
+    }
+    let a = Matrix::new("a", a_rows).unwrap();
+    let b = Matrix::new("b", b_rows).unwrap();
+    let c = Matrix::new("c", c_rows).unwrap();
+    (a, b, c, z)
+}
+
+// A satisfiable size x size R1CS drawn from a PRNG seeded with `seed`. z_0 = 1 and the rest of
+// z is random. Each row of A and B gets up to three random entries, and each row of C gets one
+// random entry plus a constant term (column 0) chosen so the row holds for z. The last
+// variable is used in every row, so changing it breaks the assignment.
+fn get_random_r1cs(
+    seed: u8,
+    size: usize,
+) -> (
+    Matrix<BaseElement>,
+    Matrix<BaseElement>,
+    Matrix<BaseElement>,
+    Vec<BaseElement>,
+) {
+    let mut z = prng_vector::<BaseElement>([seed; 32], size);
+    z[0] = BaseElement::ONE;
+    let coeffs = prng_vector::<BaseElement>([seed.wrapping_add(1); 32], 8 * size);
+    let cols = prng_vector::<u64>([seed.wrapping_add(2); 32], 8 * size);
+    let mut coeffs = coeffs.into_iter();
+    let mut cols = cols.into_iter().map(|col| col as usize % size);
+
+    let dot = |row: &[BaseElement]| -> BaseElement {
+        row.iter().zip(z.iter()).fold(BaseElement::ZERO, |acc, (&m, &v)| acc + m * v)
+    };
+    let mut a_rows = vec![vec![BaseElement::ZERO; size]; size];
+    let mut b_rows = a_rows.clone();
+    let mut c_rows = a_rows.clone();
+    for i in 0..size {
+        a_rows[i][size - 1] = coeffs.next().unwrap();
+        for _ in 0..2 {
+            a_rows[i][cols.next().unwrap()] = coeffs.next().unwrap();
+            b_rows[i][cols.next().unwrap()] = coeffs.next().unwrap();
+        }
+        b_rows[i][cols.next().unwrap()] = coeffs.next().unwrap();
+        let c_col = 1 + cols.next().unwrap() % (size - 1);
+        c_rows[i][c_col] = coeffs.next().unwrap();
+        let product = dot(&a_rows[i]) * dot(&b_rows[i]);
+        c_rows[i][0] = product - c_rows[i][c_col] * z[c_col];
+        debug_assert_eq!(dot(&c_rows[i]), product);
+    }
+    let a = Matrix::new("a", a_rows).unwrap();
+    let b = Matrix::new("b", b_rows).unwrap();
+    let c = Matrix::new("c", c_rows).unwrap();
+    (a, b, c, z)
+}
+
+fn get_example_proof(
+    prover_key: ProverKey<H, BaseElement>,
+    options: FractalOptions<BaseElement>,
+    wires: Vec<BaseElement>,
+    pub_inputs_bytes: Vec<u8>,
+) -> FractalProof<BaseElement, BaseElement, H> {
+    let mut prover = FractalProver::<BaseElement, BaseElement, H>::new(
+        prover_key,
+        options,
+        vec![],
+        wires,
+        pub_inputs_bytes,
+    );
+    prover.generate_proof().unwrap()
+}
@@ -1,34 +1,331 @@
 use crate::errors::FractalVerifierError;
 
-use fractal_indexer::snark_keys::*;
-use fractal_proofs::{FieldElement, FractalProof, StarkField};
+use fractal_indexer::{index::eval_domain_size, snark_keys::*};
+use fractal_proofs::{
+    ByteReader, Deserializable, DeserializationError, FieldElement, FieldTag, FractalProof, FriOptions, LowDegreeProof,
+    ProofOptions, SliceReader, StarkField,
+};
 
 use fractal_sumcheck::log::debug;
+use fractal_utils::channel_utils::{hash_to_field, public_inputs_from_bytes};
 use winter_crypto::{ElementHasher, RandomCoin};
 
-use crate::{lincheck_verifier::verify_lincheck_proof, rowcheck_verifier::verify_rowcheck_proof};
+use crate::{lincheck_verifier::verify_lincheck_proof_ref, rowcheck_verifier::verify_rowcheck_proof_ref};
 
+/// Verifies `proof` for the circuit `verifier_key` was generated for. `options` are the FRI
+/// options and number of queries the verifier accepts; the proof carries its own, and is
+/// rejected if they differ.
 pub fn verify_fractal_proof<
     B: StarkField,
     E: FieldElement<BaseField = B>,
     H: ElementHasher<BaseField = B>,
 >(
     verifier_key: VerifierKey<H, B>,
+    options: &ProofOptions,
     proof: FractalProof<B, E, H>,
     pub_inputs_bytes: Vec<u8>,
 ) -> Result<(), FractalVerifierError> {
-    let mut public_coin = RandomCoin::<_, H>::new(&pub_inputs_bytes);
-    let expected_alpha: B = public_coin.draw().expect("failed to draw OOD point");
-    
-    verify_rowcheck_proof(&verifier_key, proof.rowcheck_proof)?;
+    verify_fractal_proof_ref(&verifier_key, options, &proof, &pub_inputs_bytes)
+}
+
+/// The counterpart of `fractal_prover::prover::prove`: checks `proof` against the verifier key
+/// and options `prove` returned and the `pub_inputs_bytes` the proof was made with.
+pub fn verify<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: FractalProof<B, E, H>,
+    verifier_key: VerifierKey<H, B>,
+    options: &ProofOptions,
+    pub_inputs_bytes: Vec<u8>,
+) -> Result<(), FractalVerifierError> {
+    verify_fractal_proof(verifier_key, options, proof, pub_inputs_bytes)
+}
+
+/// Same as [verify_fractal_proof], but borrows its arguments, so the same proof can be
+/// verified again or inspected afterwards.
+pub fn verify_fractal_proof_ref<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    options: &ProofOptions,
+    proof: &FractalProof<B, E, H>,
+    pub_inputs_bytes: &[u8],
+) -> Result<(), FractalVerifierError> {
+    if proof.field_tag != FieldTag::of::<B, E>() {
+        return Err(FractalVerifierError::FieldMismatch);
+    }
+    validate_proof_structure(proof, options)?;
+    let public_inputs = decode_public_inputs(verifier_key, pub_inputs_bytes)?;
+    // Otherwise every challenge would differ and verification would fail somewhere in FRI.
+    if proof.pub_inputs_hash != H::hash(pub_inputs_bytes) {
+        return Err(FractalVerifierError::PublicInputMismatch);
+    }
+    let mut public_coin = RandomCoin::<_, H>::new(pub_inputs_bytes);
+    let expected_alpha: B = hash_to_field(&mut public_coin).map_err(FractalVerifierError::AlphaDrawErr)?;
+
+    check_circuit_size(verifier_key, options, proof)?;
+    // A, B and C differ, so equal gammas point at a prover bug. Each lincheck checks its gamma
+    // against its committed t_alpha at beta, see verify_lincheck_proof.
+    let gammas = [proof.lincheck_a.gamma, proof.lincheck_b.gamma, proof.lincheck_c.gamma];
+    if gammas[0] == gammas[1] || gammas[1] == gammas[2] || gammas[0] == gammas[2] {
+        debug!("Linchecks share a gamma: {:?}", gammas);
+    }
+
+    verify_rowcheck_proof_ref(verifier_key, options, &proof.rowcheck_proof, &public_inputs)?;
     debug!("Rowcheck verified");
     debug!("Lincheck a indexes: {:?}", &proof.lincheck_a.products_sumcheck_proof.queried_positions);
-    verify_lincheck_proof(&verifier_key, proof.lincheck_a, expected_alpha)?;
+    verify_lincheck_proof_ref(verifier_key, options, &verifier_key.matrix_a_commitments, &proof.lincheck_a, expected_alpha)?;
     debug!("Lincheck a verified");
-    verify_lincheck_proof(&verifier_key, proof.lincheck_b, expected_alpha)?;
+    verify_lincheck_proof_ref(verifier_key, options, &verifier_key.matrix_b_commitments, &proof.lincheck_b, expected_alpha)?;
     debug!("Lincheck b verified");
-    verify_lincheck_proof(&verifier_key, proof.lincheck_c, expected_alpha)?;
+    verify_lincheck_proof_ref(verifier_key, options, &verifier_key.matrix_c_commitments, &proof.lincheck_c, expected_alpha)?;
     debug!("Lincheck c verified");
     
     Ok(())
 }
+
+/// Reads a serialized [FractalProof] and verifies it. The field tag is checked before the
+/// rest of the proof is read, so a proof over a different field configuration is rejected
+/// with [FractalVerifierError::FieldMismatch] rather than an opaque deserialization error.
+pub fn verify_fractal_proof_bytes<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    options: &ProofOptions,
+    proof_bytes: &[u8],
+    pub_inputs_bytes: &[u8],
+) -> Result<(), FractalVerifierError> {
+    if FieldTag::read_from(&mut SliceReader::new(proof_bytes))? != FieldTag::of::<B, E>() {
+        return Err(FractalVerifierError::FieldMismatch);
+    }
+    let mut reader = SliceReader::new(proof_bytes);
+    let proof = FractalProof::<B, E, H>::read_from(&mut reader)?;
+    if reader.has_more_bytes() {
+        return Err(DeserializationError::UnconsumedBytes.into());
+    }
+    verify_fractal_proof_ref(verifier_key, options, &proof, pub_inputs_bytes)
+}
+
+/// Checks that every part of `proof` was computed over the evaluation domain the verifier key
+/// implies, i.e. `max_degree` times the verifier's FRI blowup factor. Otherwise the proof is
+/// for a circuit of a different size, which FRI would only notice indirectly.
+pub fn check_circuit_size<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    verifier_key: &VerifierKey<H, B>,
+    options: &ProofOptions,
+    proof: &FractalProof<B, E, H>,
+) -> Result<(), FractalVerifierError> {
+    let expected = eval_domain_size(verifier_key.params.max_degree, options.fri_options.blowup_factor());
+    let rowcheck = &proof.rowcheck_proof;
+    let mut domain_sizes = vec![rowcheck.num_evaluations];
+    for lincheck in [&proof.lincheck_a, &proof.lincheck_b, &proof.lincheck_c] {
+        domain_sizes.push(lincheck.num_evaluations);
+        for sumcheck in [&lincheck.products_sumcheck_proof, &lincheck.matrix_sumcheck_proof] {
+            domain_sizes.push(sumcheck.num_evaluations);
+        }
+    }
+    for actual in domain_sizes {
+        if actual != expected {
+            return Err(FractalVerifierError::CircuitSizeMismatch { expected, actual });
+        }
+    }
+    Ok(())
+}
+
+/// Cheap structural checks to run before any cryptographic verification: every part of `proof`
+/// uses the verifier's FRI options, every queried part has `options.num_queries` positions and
+/// as many values and Merkle paths, and every FRI proof has one commitment per layer the
+/// options imply, plus one for the remainder.
+pub fn validate_proof_structure<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: &FractalProof<B, E, H>,
+    options: &ProofOptions,
+) -> Result<(), FractalVerifierError> {
+    check_fri_options(proof, &options.fri_options)?;
+    let expected_queries = options.num_queries;
+    let rowcheck = &proof.rowcheck_proof;
+    for (what, actual) in [
+        ("rowcheck queried_positions", rowcheck.queried_positions.len()),
+        ("rowcheck s_original_evals", rowcheck.s_original_evals.len()),
+        ("rowcheck s_queried_evals", rowcheck.s_queried_evals.len()),
+        ("rowcheck f_az_evals", rowcheck.f_az_evals.len()),
+        ("rowcheck f_bz_evals", rowcheck.f_bz_evals.len()),
+        ("rowcheck f_cz_evals", rowcheck.f_cz_evals.len()),
+        ("rowcheck f_z_evals", rowcheck.f_z_evals.len()),
+    ] {
+        check_num_entries(what, actual, expected_queries)?;
+    }
+    check_num_entries(
+        "rowcheck s_commitments",
+        rowcheck.s_commitments.len(),
+        num_fri_commitments(&rowcheck.options, rowcheck.num_evaluations),
+    )?;
+    check_low_degree_proof_structure("rowcheck w proof", &rowcheck.w_proof, expected_queries)?;
+
+    let linchecks = [("a", &proof.lincheck_a), ("b", &proof.lincheck_b), ("c", &proof.lincheck_c)];
+    for (name, lincheck) in linchecks {
+        for (oracle, queries) in [
+            ("t_alpha", &lincheck.t_alpha_queried),
+            ("row", &lincheck.row_queried),
+            ("col", &lincheck.col_queried),
+            ("val", &lincheck.val_queried),
+        ] {
+            let what = format!("lincheck {} {} queries", name, oracle);
+            check_num_entries(&what, queries.queried_evals.len(), expected_queries)?;
+            check_num_entries(&what, queries.queried_proofs.len(), expected_queries)?;
+        }
+        for (sumcheck_name, sumcheck) in [
+            ("products", &lincheck.products_sumcheck_proof),
+            ("matrix", &lincheck.matrix_sumcheck_proof),
+        ] {
+            let what = format!("lincheck {} {} sumcheck", name, sumcheck_name);
+            check_num_entries(&what, sumcheck.queried_positions.len(), expected_queries)?;
+            let g_queries_what = format!("{} g queries", what);
+            check_num_entries(&g_queries_what, sumcheck.g_queried.queried_evals.len(), expected_queries)?;
+            check_num_entries(&g_queries_what, sumcheck.g_queried.queried_proofs.len(), expected_queries)?;
+            for (poly, ld_proof) in [("g", &sumcheck.g_proof), ("e", &sumcheck.e_proof)] {
+                check_low_degree_proof_structure(&format!("{} {} proof", what, poly), ld_proof, expected_queries)?;
+            }
+        }
+        check_low_degree_proof_structure(&format!("lincheck {} gamma proof", name), &lincheck.gamma_proof, expected_queries)?;
+    }
+    Ok(())
+}
+
+// The rowcheck, linchecks and sumchecks, and each of their low degree proofs, carry their own
+// copy of the FRI options.
+fn check_fri_options<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: &FractalProof<B, E, H>,
+    fri_options: &FriOptions,
+) -> Result<(), FractalVerifierError> {
+    let rowcheck = &proof.rowcheck_proof;
+    let mut parts = vec![
+        ("rowcheck".to_string(), &rowcheck.options),
+        ("rowcheck w proof".to_string(), &rowcheck.w_proof.options),
+    ];
+    let linchecks = [("a", &proof.lincheck_a), ("b", &proof.lincheck_b), ("c", &proof.lincheck_c)];
+    for (name, lincheck) in linchecks {
+        parts.push((format!("lincheck {}", name), &lincheck.options));
+        parts.push((format!("lincheck {} gamma proof", name), &lincheck.gamma_proof.options));
+        for (sumcheck_name, sumcheck) in [
+            ("products", &lincheck.products_sumcheck_proof),
+            ("matrix", &lincheck.matrix_sumcheck_proof),
+        ] {
+            let what = format!("lincheck {} {} sumcheck", name, sumcheck_name);
+            parts.push((format!("{} g proof", what), &sumcheck.g_proof.options));
+            parts.push((format!("{} e proof", what), &sumcheck.e_proof.options));
+            parts.push((what, &sumcheck.options));
+        }
+    }
+    match parts.into_iter().find(|(_, part_options)| *part_options != fri_options) {
+        Some((what, _)) => Err(FractalVerifierError::OptionsMismatch(what)),
+        None => Ok(()),
+    }
+}
+
+fn check_low_degree_proof_structure<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    what: &str,
+    ld_proof: &LowDegreeProof<B, E, H>,
+    expected_queries: usize,
+) -> Result<(), FractalVerifierError> {
+    check_num_entries(what, ld_proof.queried_positions.len(), expected_queries)?;
+    check_num_entries(what, ld_proof.unpadded_queried_evaluations.len(), expected_queries)?;
+    check_num_entries(what, ld_proof.padded_queried_evaluations.len(), expected_queries)?;
+    check_num_entries(
+        &format!("{} commitments", what),
+        ld_proof.commitments.len(),
+        num_fri_commitments(&ld_proof.options, ld_proof.num_evaluations),
+    )
+}
+
+fn check_num_entries(
+    what: &str,
+    actual: usize,
+    expected: usize,
+) -> Result<(), FractalVerifierError> {
+    if actual != expected {
+        return Err(FractalVerifierError::MalformedProof(format!(
+            "{} has {} entries, expected {}",
+            what, actual, expected
+        )));
+    }
+    Ok(())
+}
+
+// The prover commits to every FRI layer and then to the remainder.
+fn num_fri_commitments(options: &FriOptions, num_evaluations: usize) -> usize {
+    options.num_fri_layers(num_evaluations) + 1
+}
+
+/// If the verifier key declares public inputs, `pub_inputs_bytes` must be exactly their
+/// encoding (see `public_inputs_to_bytes`). Returns the decoded values, which the rowcheck
+/// checks z starts with. Without public inputs, `pub_inputs_bytes` only seed the transcript.
+fn decode_public_inputs<B: StarkField, H: ElementHasher<BaseField = B>>(
+    verifier_key: &VerifierKey<H, B>,
+    pub_inputs_bytes: &[u8],
+) -> Result<Vec<B>, FractalVerifierError> {
+    let num_public_inputs = verifier_key.params.num_public_inputs;
+    if num_public_inputs == 0 {
+        return Ok(Vec::new());
+    }
+    let expected = num_public_inputs * B::ELEMENT_BYTES;
+    if pub_inputs_bytes.len() != expected {
+        return Err(FractalVerifierError::PublicInputsLengthMismatch {
+            expected,
+            actual: pub_inputs_bytes.len(),
+        });
+    }
+    Ok(public_inputs_from_bytes(pub_inputs_bytes, num_public_inputs)?)
+}
+
+/// Checks that `verifier_key` is the opening of `key_commitment`.
+pub fn verify_key_commitment<
+    B: StarkField,
+    H: ElementHasher<BaseField = B>,
+>(
+    key_commitment: H::Digest,
+    verifier_key: &VerifierKey<H, B>,
+) -> Result<(), FractalVerifierError> {
+    if verifier_key.commitment() != key_commitment {
+        return Err(FractalVerifierError::VerifierKeyCommitmentErr);
+    }
+    Ok(())
+}
+
+/// Same as [verify_fractal_proof] but for a verifier that only holds a commitment
+/// to the [VerifierKey]. The full key is supplied alongside the proof as the opening
+/// and is only used once it is confirmed to match `key_commitment`.
+pub fn verify_fractal_proof_with_key_commitment<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    key_commitment: H::Digest,
+    verifier_key: VerifierKey<H, B>,
+    options: &ProofOptions,
+    proof: FractalProof<B, E, H>,
+    pub_inputs_bytes: Vec<u8>,
+) -> Result<(), FractalVerifierError> {
+    verify_key_commitment(key_commitment, &verifier_key)?;
+    verify_fractal_proof(verifier_key, options, proof, pub_inputs_bytes)
+}
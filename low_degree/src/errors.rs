@@ -1,13 +1,37 @@
+use winter_crypto::MerkleTreeError;
 use winter_fri::VerifierError;
 use winter_utils::DeserializationError;
 
 #[derive(Debug, PartialEq)]
 pub enum LowDegreeVerifierError {
-    /// Error propagation
+    /// FRI rejected the padded evaluations. Winterfell verifies all queried positions as one
+    /// batch and reports the failing layer rather than a query, so no position is attached.
     FriVerifierErr(VerifierError),
     /// Error propagation
     DeserializationErr(DeserializationError),
-    PaddingErr,
+    /// Error propagation
+    MerkleTreeErr(MerkleTreeError),
+    /// The tree proof opens a different number of leaves than there are unpadded evaluations
+    TreeLeavesMismatch,
+    /// The opened leaf at this queried position isn't the hash of the unpadded evaluation
+    TreeLeafMismatch(usize),
+    /// The padded evaluation at this queried position isn't the unpadded one times the
+    /// complementary polynomial
+    PaddingErr(usize),
+    /// The queried positions aren't the ones drawn after committing to the tree root
+    QueryPositionsMismatch,
+    /// The FRI options can't fold the padded polynomial down to the remainder
+    DegreeReductionErr(String),
+    /// The proof's FRI options aren't the ones the verifier expects
+    OptionsMismatch,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LowDegreeProverError {
+    /// The FRI options can't fold the padded polynomial down to the remainder
+    DegreeReductionErr(String),
+    /// The polynomial has degree {0}, above the maximum degree {1} it's proven against
+    DegreeTooHigh(usize, usize),
 }
 
 impl From<VerifierError> for LowDegreeVerifierError {
@@ -22,6 +46,12 @@ impl From<DeserializationError> for LowDegreeVerifierError {
     }
 }
 
+impl From<MerkleTreeError> for LowDegreeVerifierError {
+    fn from(error: MerkleTreeError) -> Self {
+        Self::MerkleTreeErr(error)
+    }
+}
+
 impl std::fmt::Display for LowDegreeVerifierError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
@@ -31,9 +61,39 @@ impl std::fmt::Display for LowDegreeVerifierError {
             LowDegreeVerifierError::DeserializationErr(err) => {
                 writeln!(f, "Winterfell Utils Deserialization Error: {}", err)
             }
-            LowDegreeVerifierError::PaddingErr => {
-                writeln!(f, "Complimentary Polynomial Check Failed")
+            LowDegreeVerifierError::MerkleTreeErr(err) => {
+                writeln!(f, "Merkle Tree Error: {}", err)
+            }
+            LowDegreeVerifierError::TreeLeavesMismatch => {
+                writeln!(f, "Number of tree leaves doesn't match the unpadded queried evaluations")
+            }
+            LowDegreeVerifierError::TreeLeafMismatch(position) => {
+                writeln!(f, "Tree leaf at position {} doesn't match the unpadded evaluation", position)
+            }
+            LowDegreeVerifierError::PaddingErr(position) => {
+                writeln!(f, "Complementary polynomial check failed at position {}", position)
+            }
+            LowDegreeVerifierError::QueryPositionsMismatch => {
+                writeln!(f, "Queried positions don't match the ones drawn from the transcript")
+            }
+            LowDegreeVerifierError::DegreeReductionErr(reason) => {
+                writeln!(f, "FRI Degree Reduction Error: {}", reason)
+            }
+            LowDegreeVerifierError::OptionsMismatch => {
+                writeln!(f, "FRI options don't match the verifier's")
             }
         }
     }
-}
\ No newline at end of file
+}
+impl std::fmt::Display for LowDegreeProverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            LowDegreeProverError::DegreeReductionErr(reason) => {
+                writeln!(f, "FRI Degree Reduction Error: {}", reason)
+            }
+            LowDegreeProverError::DegreeTooHigh(degree, max_degree) => {
+                writeln!(f, "Polynomial of degree {} is above the maximum degree {}", degree, max_degree)
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::{convert::TryInto, marker::PhantomData};
 
 use fractal_utils::polynomial_utils::*;
 use winter_crypto::{ElementHasher, Hasher, MerkleTree};
-use winter_fri::{DefaultProverChannel, FriOptions};
+use winter_fri::{DefaultProverChannel, FriOptions, ProverChannel};
 use winter_math::{fft, FieldElement, StarkField};
 use winter_utils::{transpose_slice};
 use fractal_indexer::hash_values;
@@ -11,6 +11,9 @@ use fractal_indexer::hash_values;
 
 use fractal_proofs::{OracleQueries, LowDegreeProof, polynom::{self, eval}};
 
+use crate::errors::{LowDegreeProverError, LowDegreeVerifierError};
+use crate::low_degree_verifier::check_fri_degree_reduction;
+
 pub struct LowDegreeProver<
     B: StarkField,
     E: FieldElement<BaseField = B>,
@@ -39,13 +42,13 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
         evaluation_domain: &Vec<B>,
         max_degree: usize,
         fri_options: FriOptions,
-    ) -> Self {
+    ) -> Result<Self, LowDegreeProverError> {
+        let fri_max_degree = evaluation_domain.len() / fri_options.blowup_factor() -1;
+        check_prover_degrees(evaluation_domain.len(), polynom::degree_of(&polynomial), max_degree, fri_max_degree, &fri_options)?;
         let polynomial_evals = polynom::eval_many(&polynomial, &evaluation_domain).iter().map(|x| E::from(*x)).collect();
         let polynomial_e = polynomial.iter().map(|c| E::from(*c)).collect();
-        let fri_max_degree = evaluation_domain.len() / fri_options.blowup_factor() -1;
-        assert!(polynom::degree_of(&polynomial) <= max_degree);
         let evaluation_domain_e = evaluation_domain.iter().map(|y| E::from(*y)).collect();
-        LowDegreeProver {
+        Ok(LowDegreeProver {
             polynomial_coeffs: polynomial_e,
             polynomial_evals,
             evaluation_domain: evaluation_domain_e,
@@ -53,7 +56,7 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
             fri_max_degree,
             fri_options,
             _h: PhantomData
-        }
+        })
     }
 
     pub fn from_evals(
@@ -61,12 +64,12 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
         evaluation_domain: &Vec<E>,
         max_degree: usize,
         fri_options: FriOptions,
-    ) -> Self {
+    ) -> Result<Self, LowDegreeProverError> {
         assert_eq!(polynomial_evals.len(), evaluation_domain.len());
         let polynomial_coeffs = polynom::interpolate(&evaluation_domain, &polynomial_evals, true);
-        assert!(polynom::degree_of(&polynomial_coeffs) <= max_degree);
         let fri_max_degree = evaluation_domain.len() / fri_options.blowup_factor() -1;
-        LowDegreeProver {
+        check_prover_degrees(evaluation_domain.len(), polynom::degree_of(&polynomial_coeffs), max_degree, fri_max_degree, &fri_options)?;
+        Ok(LowDegreeProver {
             polynomial_coeffs,
             polynomial_evals,
             evaluation_domain: evaluation_domain.clone(),
@@ -74,21 +77,22 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
             fri_max_degree,
             fri_options,
             _h: PhantomData
-        }
+        })
     }
 
     pub fn generate_proof(&self, channel: &mut DefaultProverChannel<B, E, H>) -> LowDegreeProof<B, E, H> {
+        let tree = self.build_tree();
+        let tree_root = *tree.root();
+        // Commit to the unpadded evaluations before the queries are drawn, so they can't be
+        // picked after the queried positions are known.
+        channel.commit_fri_layer(tree_root);
+
         let queried_positions = channel.draw_query_positions();
         let commitment_idx = channel.layer_commitments().len();
         let unpadded_queried_evaluations = queried_positions
             .iter()
             .map(|&p| self.polynomial_evals[p])
             .collect::<Vec<_>>();
-
-        let transposed_evaluations = transpose_slice(&self.polynomial_evals);
-        let hashed_evaluations = hash_values::<H, E, 1>(&transposed_evaluations);
-        let tree = MerkleTree::<H>::new(hashed_evaluations).unwrap();
-        let tree_root = *tree.root();
         let tree_proof = tree.prove_batch(&queried_positions).unwrap();
 
         let comp_coeffs = get_complementary_poly::<E>(self.max_degree, self.fri_max_degree);
@@ -120,4 +124,40 @@ impl<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher<BaseField =
             fri_max_degree: self.fri_max_degree,
         }
     }
-}
\ No newline at end of file
+
+    /// Opens the unpadded evaluations at `positions`, with one path per position against the
+    /// tree_root of the proof generate_proof makes. Other checks use this to read the polynomial
+    /// at positions its own proof didn't query.
+    pub fn open(&self, positions: &[usize]) -> OracleQueries<B, E, H> {
+        let tree = self.build_tree();
+        let evals = positions.iter().map(|&p| self.polynomial_evals[p]).collect();
+        let paths = positions.iter().map(|&p| tree.prove(p).unwrap()).collect();
+        OracleQueries::new(evals, paths).unwrap()
+    }
+
+    fn build_tree(&self) -> MerkleTree<H> {
+        let transposed_evaluations = transpose_slice(&self.polynomial_evals);
+        let hashed_evaluations = hash_values::<H, E, 1>(&transposed_evaluations);
+        MerkleTree::<H>::new(hashed_evaluations).unwrap()
+    }
+}
+
+// The same degree reduction check the verifier runs, so a prover with unusable FRI options
+// fails here instead of producing a proof nobody accepts.
+fn check_prover_degrees(
+    num_evaluations: usize,
+    degree: usize,
+    max_degree: usize,
+    fri_max_degree: usize,
+    fri_options: &FriOptions,
+) -> Result<(), LowDegreeProverError> {
+    if let Err(LowDegreeVerifierError::DegreeReductionErr(reason)) =
+        check_fri_degree_reduction(num_evaluations, fri_max_degree, fri_options)
+    {
+        return Err(LowDegreeProverError::DegreeReductionErr(reason));
+    }
+    if degree > max_degree {
+        return Err(LowDegreeProverError::DegreeTooHigh(degree, max_degree));
+    }
+    Ok(())
+}
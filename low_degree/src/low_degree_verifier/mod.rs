@@ -1,36 +1,161 @@
 use crate::errors::LowDegreeVerifierError;
 
-use fractal_proofs::{FieldElement, LowDegreeProof, polynom};
+use fractal_indexer::index::eval_domain_size;
+use log::{debug, trace};
+use fractal_proofs::{FieldElement, LowDegreeProof, ProofOptions, polynom};
 use fractal_utils::polynomial_utils::*;
-use winter_crypto::{ElementHasher, RandomCoin};
-use winter_fri::{DefaultVerifierChannel, FriVerifier};
+use winter_crypto::{ElementHasher, MerkleTree, RandomCoin};
+use winter_fri::{DefaultVerifierChannel, FriOptions, FriVerifier};
 use winter_math::StarkField;
 
 // public_coin is used similarly to a proving channel. Why is that?
+/// The proof must use `options.fri_options` and open exactly `options.num_queries` positions.
 pub fn verify_low_degree_proof<
     B: StarkField,
     E: FieldElement<BaseField = B>,
     H: ElementHasher<BaseField = B>,
 >(
-    proof: LowDegreeProof<B, E, H>, max_degree: usize, public_coin: &mut RandomCoin<B,H>
+    proof: LowDegreeProof<B, E, H>, max_degree: usize, options: &ProofOptions, public_coin: &mut RandomCoin<B,H>
 ) -> Result<(), LowDegreeVerifierError> {
+    verify_low_degree_proof_ref(&proof, max_degree, options, public_coin)
+}
+
+/// Same as [verify_low_degree_proof], but borrows the proof so it can be verified again or
+/// inspected afterwards. Winterfell's verifier channel needs its own copy of the FRI proof,
+/// so only that part is cloned.
+pub fn verify_low_degree_proof_ref<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    proof: &LowDegreeProof<B, E, H>, max_degree: usize, options: &ProofOptions, public_coin: &mut RandomCoin<B,H>
+) -> Result<(), LowDegreeVerifierError> {
+    // before the channel splits the FRI proof by a folding factor it wasn't made with
+    check_options(proof, options)?;
     let mut channel = DefaultVerifierChannel::<E, H>::new(
-        proof.fri_proof,
-        proof.commitments,
+        proof.fri_proof.clone(),
+        proof.commitments.clone(),
         proof.num_evaluations,
-        proof.options.folding_factor(),
+        options.fri_options.folding_factor(),
     )?;
+    verify_low_degree_proof_with_channel(&mut channel, proof, max_degree, options, public_coin)
+}
+
+/// Verifies a low degree proof whose FRI layers are read from an already constructed
+/// winterfell `DefaultVerifierChannel`, so callers holding one don't need to rebuild it
+/// from the proof. Everything else, including the tree root, is read from `proof`.
+pub fn verify_low_degree_proof_with_channel<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    channel: &mut DefaultVerifierChannel<E, H>,
+    proof: &LowDegreeProof<B, E, H>,
+    max_degree: usize,
+    options: &ProofOptions,
+    public_coin: &mut RandomCoin<B,H>,
+) -> Result<(), LowDegreeVerifierError> {
+    check_options(proof, options)?;
+    check_fri_degree_reduction(proof.num_evaluations, proof.fri_max_degree, &proof.options)?;
+    verify_query_positions(proof, options.num_queries, public_coin)?;
+    verify_tree_root(proof)?;
+    debug!(
+        "FRI remainder degree {:?}, fri_max_degree {}",
+        proof.remainder_degree(),
+        proof.fri_max_degree
+    );
+    verify_fri_and_padding::<B, E, H>(
+        channel,
+        &proof.options,
+        proof.fri_max_degree,
+        max_degree,
+        &proof.unpadded_queried_evaluations,
+        &proof.padded_queried_evaluations,
+        &proof.queried_positions,
+        public_coin,
+    )
+}
 
+fn verify_fri_and_padding<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(
+    channel: &mut DefaultVerifierChannel<E, H>,
+    options: &FriOptions,
+    fri_max_degree: usize,
+    max_degree: usize,
+    unpadded_queried_evaluations: &[E],
+    padded_queried_evaluations: &[E],
+    queried_positions: &[usize],
+    public_coin: &mut RandomCoin<B,H>,
+) -> Result<(), LowDegreeVerifierError> {
     let fri_verifier = FriVerifier::<B, E, DefaultVerifierChannel<E, H>, H>::new(
-        &mut channel,
+        channel,
         public_coin,
-        proof.options.clone(),
-        proof.fri_max_degree,
+        options.clone(),
+        fri_max_degree,
     )?;
     //todo, are the queried position ever checked?
-    fri_verifier.verify(&mut channel, &proof.padded_queried_evaluations, &proof.queried_positions)?;
-    if max_degree < proof.fri_max_degree{
-        verify_lower_degree::<B, E, H>(proof.options.blowup_factor() * (proof.fri_max_degree+1), max_degree, proof.fri_max_degree, proof.unpadded_queried_evaluations, proof.padded_queried_evaluations, proof.queried_positions.clone())?;
+    fri_verifier.verify(channel, padded_queried_evaluations, queried_positions)?;
+    if max_degree < fri_max_degree{
+        verify_lower_degree::<B, E, H>(eval_domain_size(fri_max_degree + 1, options.blowup_factor()), max_degree, fri_max_degree, unpadded_queried_evaluations, padded_queried_evaluations, queried_positions)?;
+    }
+    Ok(())
+}
+
+// The proof carries a copy of its FRI options, which must be the verifier's.
+fn check_options<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(proof: &LowDegreeProof<B, E, H>, options: &ProofOptions) -> Result<(), LowDegreeVerifierError> {
+    if proof.options != options.fri_options {
+        return Err(LowDegreeVerifierError::OptionsMismatch);
+    }
+    Ok(())
+}
+
+/// The prover commits to `proof.tree_root` and then draws the queried positions, see
+/// [LowDegreeProver::generate_proof](crate::low_degree_prover::LowDegreeProver::generate_proof).
+/// Replays that on `public_coin` and checks the proof was opened at the `num_queries` positions
+/// drawn. The count is the verifier's, since a prover that picks it could open a single position.
+fn verify_query_positions<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(proof: &LowDegreeProof<B, E, H>, num_queries: usize, public_coin: &mut RandomCoin<B,H>) -> Result<(), LowDegreeVerifierError> {
+    public_coin.reseed(proof.tree_root);
+    if num_queries == 0 || num_queries >= proof.num_evaluations || proof.queried_positions.len() != num_queries {
+        return Err(LowDegreeVerifierError::QueryPositionsMismatch);
+    }
+    let expected_positions = public_coin
+        .draw_integers(num_queries, proof.num_evaluations)
+        .map_err(|_| LowDegreeVerifierError::QueryPositionsMismatch)?;
+    if expected_positions != proof.queried_positions {
+        return Err(LowDegreeVerifierError::QueryPositionsMismatch);
+    }
+    Ok(())
+}
+
+/// Checks that `proof.tree_proof` opens `proof.tree_root` at the queried positions, and that
+/// the opened leaves are the hashes of the unpadded queried evaluations. On its own this only
+/// shows the evaluations are in some tree; they're bound to the proof because the queried
+/// positions are drawn after the root is committed, which [verify_low_degree_proof] checks.
+pub fn verify_tree_root<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+>(proof: &LowDegreeProof<B, E, H>) -> Result<(), LowDegreeVerifierError> {
+    MerkleTree::<H>::verify_batch(&proof.tree_root, &proof.queried_positions, &proof.tree_proof)?;
+    if proof.tree_proof.leaves.len() != proof.unpadded_queried_evaluations.len() {
+        return Err(LowDegreeVerifierError::TreeLeavesMismatch);
+    }
+    let leaves = proof.tree_proof.leaves.iter().zip(proof.unpadded_queried_evaluations.iter());
+    for ((leaf, eval), &position) in leaves.zip(proof.queried_positions.iter()) {
+        if *leaf != H::hash_elements(&[*eval]) {
+            return Err(LowDegreeVerifierError::TreeLeafMismatch(position));
+        }
     }
     Ok(())
 }
@@ -40,31 +165,83 @@ fn verify_lower_degree<
     E: FieldElement<BaseField = B>,
     H: ElementHasher<BaseField = B>,
 >(eval_domain_size: usize, original_degree: usize, fri_max_degree: usize, 
-    original_evals: Vec<E>, final_evals: Vec<E>, positions: Vec<usize>) -> Result<(), LowDegreeVerifierError> {
+    original_evals: &[E], final_evals: &[E], positions: &[usize]) -> Result<(), LowDegreeVerifierError> {
     let comp_poly = get_complementary_poly::<E>(original_degree, fri_max_degree);
-    let eval_domain_base = E::from(B::get_root_of_unity(eval_domain_size.trailing_zeros()));
-    let eval_domain_pows = positions.iter().map(|&x| x as u64).collect::<Vec<u64>>();
-    let eval_domain_elts = eval_domain_pows.iter().map(|&x| eval_domain_base.exp(E::PositiveInteger::from(x))).collect::<Vec<E>>();
+    let eval_domain_elts = get_queried_domain_elements::<B, E>(eval_domain_size, B::ONE, positions);
     let eval_domain_evals = polynom::eval_many(&comp_poly, &eval_domain_elts);
     for (pos, _) in eval_domain_elts.iter().enumerate() {
         if original_evals[pos].mul(eval_domain_evals[pos]) != final_evals[pos] {
-            println!("Position {}", pos);
-            println!("Original_evals = {:?}", original_evals);
-            println!("Domain elt = {:?}", eval_domain_elts[pos]);
-            println!("Mul = {:?}", original_evals[pos].mul(eval_domain_evals[pos]));
-            println!("Final evals = {:?}", final_evals[pos]);
-            return Err(LowDegreeVerifierError::PaddingErr);//::SmallPolyAdjustmentErr());
+            debug!(
+                "Position {}: original eval {:?} at domain elt {:?}, mul = {:?}, final eval = {:?}",
+                pos,
+                original_evals[pos],
+                eval_domain_elts[pos],
+                original_evals[pos].mul(eval_domain_evals[pos]),
+                final_evals[pos]
+            );
+            trace!("Original_evals = {:?}", original_evals);
+            return Err(LowDegreeVerifierError::PaddingErr(positions[pos]));
         }
     }
     Ok(())
 }
 
+/// Checks up front that FRI can fold a polynomial of degree `fri_max_degree`, evaluated over
+/// `num_evaluations` points, down to the remainder with the given options. Winterfell only
+/// notices this once it has read all the layer commitments.
+pub fn check_fri_degree_reduction(
+    num_evaluations: usize,
+    fri_max_degree: usize,
+    options: &FriOptions,
+) -> Result<(), LowDegreeVerifierError> {
+    let expected_evaluations = (fri_max_degree + 1).next_power_of_two() * options.blowup_factor();
+    if num_evaluations != expected_evaluations {
+        return Err(LowDegreeVerifierError::DegreeReductionErr(format!(
+            "{} evaluations don't match degree {} with blowup factor {}",
+            num_evaluations,
+            fri_max_degree,
+            options.blowup_factor()
+        )));
+    }
+    let mut degree_plus_1 = fri_max_degree + 1;
+    for layer in 0..options.num_fri_layers(num_evaluations) {
+        if degree_plus_1 % options.folding_factor() != 0 {
+            return Err(LowDegreeVerifierError::DegreeReductionErr(format!(
+                "degree {} can't be folded by {} at layer {}",
+                fri_max_degree,
+                options.folding_factor(),
+                layer
+            )));
+        }
+        degree_plus_1 /= options.folding_factor();
+    }
+    Ok(())
+}
+
+/// Maps queried positions to the elements of the evaluation domain they refer to, i.e.
+/// position `i` maps to `offset * g^i` where `g` generates the subgroup of size `eval_domain_size`.
+/// The FRI evaluation domain in this crate is not shifted, so callers currently pass `B::ONE` as the
+/// offset; the parameter is here so that every verifier performs this mapping in the same way.
+pub fn get_queried_domain_elements<B: StarkField, E: FieldElement<BaseField = B>>(
+    eval_domain_size: usize,
+    eval_domain_offset: B,
+    positions: &[usize],
+) -> Vec<E> {
+    let eval_domain_base = E::from(B::get_root_of_unity(eval_domain_size.trailing_zeros()));
+    positions
+        .iter()
+        .map(|&x| eval_domain_base.exp(E::PositiveInteger::from(x as u64)) * E::from(eval_domain_offset))
+        .collect()
+}
+
 #[cfg(test)]
 mod test{
     use crate::low_degree_prover::LowDegreeProver;
-    use super::verify_low_degree_proof;
-    use fractal_proofs::{FieldElement, SumcheckProof};
-    use winter_crypto::{ElementHasher, RandomCoin};
+    use crate::errors::{LowDegreeProverError, LowDegreeVerifierError};
+    use super::{check_fri_degree_reduction, get_queried_domain_elements, verify_low_degree_proof, verify_low_degree_proof_with_channel, verify_lower_degree, verify_tree_root};
+    use fractal_indexer::index::eval_domain_size;
+    use fractal_proofs::{polynom, ByteReader, ProofOptions, Deserializable, FieldElement, LowDegreeProof, Serializable, SliceReader, SumcheckProof};
+    use winter_crypto::{ElementHasher, Hasher, MerkleTree, RandomCoin};
     use winter_fri::{DefaultVerifierChannel, FriVerifier, FriOptions, DefaultProverChannel};
     use winter_math::StarkField;
     use winter_math::fields::f64::BaseElement;
@@ -93,15 +270,273 @@ mod test{
         let mut public_coin = RandomCoin::<B,H>::new(&[]);
 
         let mut channel = DefaultProverChannel::<B,E,H>::new(evaluation_domain.len(), num_queries);
-        let prover = LowDegreeProver::<B, E, H>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options.clone());
+        let prover = LowDegreeProver::<B, E, H>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options.clone()).unwrap();
         let proof = prover.generate_proof(&mut channel);
-        assert!(verify_low_degree_proof(proof, 63, &mut public_coin).is_ok());
+        assert!(verify_low_degree_proof(proof, 63, &ProofOptions::new(fri_options.clone(), num_queries), &mut public_coin).is_ok());
 
         let max_degree2 = 17;
         let poly2 = nonrand_poly(max_degree2);
-        let prover = LowDegreeProver::<B, E, H>::from_polynomial(&poly2, &evaluation_domain, max_degree2, fri_options.clone());
+        let prover = LowDegreeProver::<B, E, H>::from_polynomial(&poly2, &evaluation_domain, max_degree2, fri_options.clone()).unwrap();
         let proof2 = prover.generate_proof(&mut channel);
-        assert!(verify_low_degree_proof(proof2, 17, &mut public_coin).is_ok());
+        assert!(verify_low_degree_proof(proof2, 17, &ProofOptions::new(fri_options, num_queries), &mut public_coin).is_ok());
+    }
+
+    #[test]
+    fn run_test_low_degree_proof_with_channel(){
+        test_low_degree_proof_with_channel::<BaseElement, BaseElement, Rp64_256>();
+    }
+
+    fn test_low_degree_proof_with_channel<
+        B: StarkField,
+        E: FieldElement<BaseField = B>,
+        H: ElementHasher<BaseField = B>,
+        >() {
+        let fri_options = FriOptions::new(4, 4, 32);
+        let max_degree = 31;
+        let poly = nonrand_poly(max_degree);
+        let l_field_size: usize = 4 * 64;
+        let l_field_base = B::get_root_of_unity(l_field_size.trailing_zeros());
+        let evaluation_domain = utils::get_power_series(l_field_base, l_field_size);
+
+        let mut prover_channel = DefaultProverChannel::<B,E,H>::new(evaluation_domain.len(), 16);
+        let prover = LowDegreeProver::<B, E, H>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options.clone()).unwrap();
+        let proof = prover.generate_proof(&mut prover_channel);
+
+        // A channel set up by the caller directly from the winterfell types
+        let mut channel = DefaultVerifierChannel::<E, H>::new(
+            proof.fri_proof.clone(),
+            proof.commitments.clone(),
+            proof.num_evaluations,
+            fri_options.folding_factor(),
+        ).unwrap();
+        let options = ProofOptions::new(fri_options.clone(), 16);
+        let mut public_coin = RandomCoin::<B,H>::new(&[]);
+        let adaptor_result = verify_low_degree_proof_with_channel(
+            &mut channel,
+            &proof,
+            max_degree,
+            &options,
+            &mut public_coin,
+        );
+
+        let mut public_coin = RandomCoin::<B,H>::new(&[]);
+        let standard_result = verify_low_degree_proof(proof, max_degree, &options, &mut public_coin);
+        assert!(adaptor_result.is_ok());
+        assert_eq!(adaptor_result, standard_result);
+    }
+
+    #[test]
+    fn test_reject_mismatched_tree_root(){
+        let fri_options = FriOptions::new(4, 4, 32);
+        let max_degree = 31;
+        let poly = nonrand_poly::<BaseElement>(max_degree);
+        let evaluation_domain = utils::get_power_series(BaseElement::get_root_of_unity(8), 256);
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options).unwrap();
+        let generate_proof = || {
+            let mut channel = DefaultProverChannel::<BaseElement, BaseElement, Rp64_256>::new(evaluation_domain.len(), 16);
+            prover.generate_proof(&mut channel)
+        };
+        assert!(verify_tree_root(&generate_proof()).is_ok());
+
+        let mut bad_root = generate_proof();
+        bad_root.tree_root = Rp64_256::hash(&[1u8]);
+        assert!(matches!(verify_tree_root(&bad_root), Err(LowDegreeVerifierError::MerkleTreeErr(_))));
+        let mut public_coin = RandomCoin::<BaseElement, Rp64_256>::new(&[]);
+        assert!(verify_low_degree_proof(bad_root, max_degree, &ProofOptions::new(FriOptions::new(4, 4, 32), 16), &mut public_coin).is_err());
+
+        // a consistent tree over different evaluations is rejected too
+        let mut bad_evals = generate_proof();
+        bad_evals.unpadded_queried_evaluations[0] += BaseElement::ONE;
+        let position = bad_evals.queried_positions[0];
+        assert_eq!(verify_tree_root(&bad_evals), Err(LowDegreeVerifierError::TreeLeafMismatch(position)));
+    }
+
+    #[test]
+    fn test_padding_error_reports_position(){
+        let fri_options = FriOptions::new(4, 4, 32);
+        let max_degree = 17;
+        let poly = nonrand_poly::<BaseElement>(max_degree);
+        let evaluation_domain = utils::get_power_series(BaseElement::get_root_of_unity(7), 128);
+        let mut channel = DefaultProverChannel::<BaseElement, BaseElement, Rp64_256>::new(evaluation_domain.len(), 16);
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options.clone()).unwrap();
+        let mut proof = prover.generate_proof(&mut channel);
+        assert!(proof.fri_max_degree > max_degree);
+        let check_padding = |proof: &LowDegreeProof<BaseElement, BaseElement, Rp64_256>| {
+            verify_lower_degree::<BaseElement, BaseElement, Rp64_256>(
+                eval_domain_size(proof.fri_max_degree + 1, fri_options.blowup_factor()),
+                max_degree,
+                proof.fri_max_degree,
+                &proof.unpadded_queried_evaluations,
+                &proof.padded_queried_evaluations,
+                &proof.queried_positions,
+            )
+        };
+        assert!(check_padding(&proof).is_ok());
+
+        proof.unpadded_queried_evaluations[2] += BaseElement::ONE;
+        assert_eq!(
+            check_padding(&proof),
+            Err(LowDegreeVerifierError::PaddingErr(proof.queried_positions[2]))
+        );
+    }
+
+    #[test]
+    fn test_reject_tree_rebuilt_after_queries(){
+        let fri_options = FriOptions::new(4, 4, 32);
+        let max_degree = 31;
+        let poly = nonrand_poly::<BaseElement>(max_degree);
+        let evaluation_domain = utils::get_power_series(BaseElement::get_root_of_unity(8), 256);
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options).unwrap();
+        let mut channel = DefaultProverChannel::<BaseElement, BaseElement, Rp64_256>::new(evaluation_domain.len(), 16);
+        let mut proof = prover.generate_proof(&mut channel);
+
+        // Knowing the queries, build a fresh tree over evaluations that differ at every one
+        let mut evals = polynom::eval_many(&poly, &evaluation_domain);
+        for &position in proof.queried_positions.iter() {
+            evals[position] += BaseElement::ONE;
+        }
+        let leaves = evals.iter().map(|eval| Rp64_256::hash_elements(&[*eval])).collect();
+        let tree = MerkleTree::<Rp64_256>::new(leaves).unwrap();
+        proof.tree_root = *tree.root();
+        proof.tree_proof = tree.prove_batch(&proof.queried_positions).unwrap();
+        proof.unpadded_queried_evaluations = proof.queried_positions.iter().map(|&p| evals[p]).collect();
+        assert!(verify_tree_root(&proof).is_ok());
+
+        let mut public_coin = RandomCoin::<BaseElement, Rp64_256>::new(&[]);
+        assert_eq!(
+            verify_low_degree_proof(proof, max_degree, &ProofOptions::new(FriOptions::new(4, 4, 32), 16), &mut public_coin),
+            Err(LowDegreeVerifierError::QueryPositionsMismatch)
+        );
+    }
+
+    #[test]
+    fn test_reject_prover_chosen_options(){
+        let fri_options = FriOptions::new(4, 4, 32);
+        let max_degree = 31;
+        let poly = nonrand_poly::<BaseElement>(max_degree);
+        let evaluation_domain = utils::get_power_series(BaseElement::get_root_of_unity(8), 256);
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options.clone()).unwrap();
+        let verify = |num_queries: usize, options: &ProofOptions| {
+            let mut channel = DefaultProverChannel::<BaseElement, BaseElement, Rp64_256>::new(evaluation_domain.len(), num_queries);
+            let proof = prover.generate_proof(&mut channel);
+            let mut public_coin = RandomCoin::<BaseElement, Rp64_256>::new(&[]);
+            verify_low_degree_proof(proof, max_degree, options, &mut public_coin)
+        };
+        let options = ProofOptions::new(fri_options, 16);
+        assert!(verify(16, &options).is_ok());
+
+        // a proof opened at a single position is honest about its queries, but too weak
+        assert_eq!(verify(1, &options), Err(LowDegreeVerifierError::QueryPositionsMismatch));
+        assert!(verify(1, &ProofOptions::new(options.fri_options.clone(), 1)).is_ok());
+
+        // the verifier's blowup or folding factor, not the proof's
+        let other_blowup = ProofOptions::new(FriOptions::new(8, 4, 32), 16);
+        assert_eq!(verify(16, &other_blowup), Err(LowDegreeVerifierError::OptionsMismatch));
+        let other_folding = ProofOptions::new(FriOptions::new(4, 8, 32), 16);
+        assert_eq!(verify(16, &other_folding), Err(LowDegreeVerifierError::OptionsMismatch));
+    }
+
+    #[test]
+    fn test_verify_deserialized_proof(){
+        let fri_options = FriOptions::new(4, 4, 32);
+        let max_degree = 17;
+        let poly = nonrand_poly::<BaseElement>(max_degree);
+        let evaluation_domain = utils::get_power_series(BaseElement::get_root_of_unity(7), 128);
+        let mut channel = DefaultProverChannel::<BaseElement, BaseElement, Rp64_256>::new(evaluation_domain.len(), 16);
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options).unwrap();
+        let proof = prover.generate_proof(&mut channel);
+
+        let bytes = proof.to_bytes();
+        let mut reader = SliceReader::new(&bytes);
+        let read_proof = LowDegreeProof::<BaseElement, BaseElement, Rp64_256>::read_from(&mut reader).unwrap();
+        assert!(!reader.has_more_bytes());
+        assert_eq!(read_proof.to_bytes(), bytes);
+        let mut public_coin = RandomCoin::<BaseElement, Rp64_256>::new(&[]);
+        assert!(verify_low_degree_proof(read_proof, max_degree, &ProofOptions::new(FriOptions::new(4, 4, 32), 16), &mut public_coin).is_ok());
+    }
+
+    #[test]
+    fn test_serialize_positions_above_255(){
+        let fri_options = FriOptions::new(4, 4, 32);
+        let max_degree = 511;
+        let poly = nonrand_poly::<BaseElement>(max_degree);
+        let evaluation_domain = utils::get_power_series(BaseElement::get_root_of_unity(11), 2048);
+        let mut channel = DefaultProverChannel::<BaseElement, BaseElement, Rp64_256>::new(evaluation_domain.len(), 16);
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options).unwrap();
+        let proof = prover.generate_proof(&mut channel);
+        assert!(proof.queried_positions.iter().any(|&pos| pos > 255));
+
+        let bytes = proof.to_bytes();
+        let mut reader = SliceReader::new(&bytes);
+        // FRI options come first
+        reader.read_u8_vec(12).unwrap();
+        assert_eq!(reader.read_u32().unwrap(), 2048);
+        assert_eq!(reader.read_u32().unwrap() as usize, proof.queried_positions.len());
+        for &pos in proof.queried_positions.iter() {
+            assert_eq!(reader.read_u32().unwrap() as usize, pos);
+        }
+    }
+
+    #[test]
+    fn test_remainder_degree_within_bounds(){
+        let fri_options = FriOptions::new(4, 4, 32);
+        let max_degree = 63;
+        let poly = nonrand_poly::<BaseElement>(max_degree);
+        let evaluation_domain = utils::get_power_series(BaseElement::get_root_of_unity(8), 256);
+        let mut channel = DefaultProverChannel::<BaseElement, BaseElement, Rp64_256>::new(evaluation_domain.len(), 16);
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, max_degree, fri_options.clone()).unwrap();
+        let proof = prover.generate_proof(&mut channel);
+
+        let num_layers = fri_options.num_fri_layers(proof.num_evaluations);
+        let remainder = proof.remainder().unwrap();
+        assert_eq!(remainder.len(), fri_options.fri_remainder_size(proof.num_evaluations));
+        let max_remainder_degree_plus_1 = (proof.fri_max_degree + 1) / fri_options.folding_factor().pow(num_layers as u32);
+        assert!(proof.remainder_degree().unwrap() < max_remainder_degree_plus_1);
+    }
+
+    #[test]
+    fn test_fri_degree_reduction(){
+        // 256 -> 64 -> 16 -> 4, but a degree 15 polynomial can only be folded twice by 4
+        let fri_options = FriOptions::new(16, 4, 8);
+        assert!(check_fri_degree_reduction(256, 15, &fri_options).is_err());
+        // the number of evaluations has to match the degree and blowup factor
+        let fri_options = FriOptions::new(4, 4, 32);
+        assert!(check_fri_degree_reduction(128, 63, &fri_options).is_err());
+        assert!(check_fri_degree_reduction(256, 63, &fri_options).is_ok());
+    }
+
+    #[test]
+    fn test_low_degree_prover_rejects_incompatible_fri_options(){
+        let evaluation_domain = utils::get_power_series(BaseElement::get_root_of_unity(8), 256);
+        let poly = nonrand_poly::<BaseElement>(15);
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, 15, FriOptions::new(16, 4, 8));
+        assert!(matches!(prover, Err(LowDegreeProverError::DegreeReductionErr(_))));
+
+        // a polynomial above the degree it's proven against
+        let prover = LowDegreeProver::<BaseElement, BaseElement, Rp64_256>::from_polynomial(&poly, &evaluation_domain, 14, FriOptions::new(4, 4, 32));
+        assert!(matches!(prover, Err(LowDegreeProverError::DegreeTooHigh(15, 14))));
+    }
+
+    #[test]
+    fn test_queried_domain_elements_with_offset(){
+        let eval_domain_size: usize = 64;
+        let eval_domain_base = BaseElement::get_root_of_unity(eval_domain_size.trailing_zeros());
+        let positions = vec![0, 1, 5, 17, 63];
+
+        // unshifted domain, as used by FRI in this crate
+        let domain = utils::get_power_series(eval_domain_base, eval_domain_size);
+        let elts = get_queried_domain_elements::<BaseElement, BaseElement>(eval_domain_size, BaseElement::ONE, &positions);
+        for (elt, &pos) in elts.iter().zip(positions.iter()) {
+            assert_eq!(*elt, domain[pos]);
+        }
+
+        // a coset of the same subgroup
+        let offset = BaseElement::GENERATOR;
+        let coset = utils::get_power_series_with_offset(eval_domain_base, offset, eval_domain_size);
+        let elts = get_queried_domain_elements::<BaseElement, BaseElement>(eval_domain_size, offset, &positions);
+        for (elt, &pos) in elts.iter().zip(positions.iter()) {
+            assert_eq!(*elt, coset[pos]);
+        }
     }
 
     // a random-ish polynomial that isn't actually random at all. Instead, it uses the system clock since that doesn't require a new crate import
@@ -10,6 +10,8 @@ pub enum R1CSError {
     InvalidMatrix(String),
     /// All matrices in R1CS should have equal dimensions
     MatrixSizeMismatch(String, String),
+    /// Tried to set {0} {1} of an assignment that only has {2}
+    AssignmentIndexOutOfRange(String, usize, usize),
 }
 
 /// Represents errors in instantiating input wire value vectors
@@ -56,6 +56,14 @@ impl<E: StarkField> Matrix<E> {
         self.dims.1
     }
 
+    pub fn row(&self, i: usize) -> &[E] {
+        &self.mat[i]
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> E {
+        self.mat[i][j]
+    }
+
     pub fn get_total_size(&self) -> usize {
         let rows = self.dims.0;
         let cols = self.dims.1;
@@ -149,6 +157,38 @@ impl<E: StarkField> Matrix<E> {
         }
     }
 
+    /// Dense grid of the matrix for debugging small circuits: a header with the name and
+    /// dimensions, then one line per row. Zeros are shown as `.` so the non-zero entries
+    /// stand out, and all columns are right-aligned to the widest entry.
+    pub fn to_pretty_string(&self) -> String {
+        let cells: Vec<Vec<String>> = self
+            .mat
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|elt| {
+                        if *elt == E::ZERO {
+                            String::from(".")
+                        } else {
+                            format!("{:?}", elt.as_int())
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let width = cells.iter().flatten().map(|cell| cell.len()).max().unwrap_or(1);
+        let mut out = format!("{} ({} x {})\n", self.name, self.dims.0, self.dims.1);
+        for row in cells {
+            let line: Vec<String> = row
+                .iter()
+                .map(|cell| format!("{:>width$}", cell, width = width))
+                .collect();
+            out.push_str(&line.join(" "));
+            out.push('\n');
+        }
+        out
+    }
+
     pub fn debug_print_bits(&self) {
         println!("{}", self.name);
         for row in &self.mat {
@@ -327,6 +367,63 @@ impl<E: StarkField> R1CS<E> {
     }
 }
 
+/// A variable assignment z = (1, x, w) for an R1CS instance, laid out as jsnark lays out its
+/// wires: wire 0 is the constant one, followed by the public inputs x and then the witness w.
+/// Constraints that use constants refer to wire 0, so it has to hold 1 for them to be
+/// satisfied. The constant and the public inputs together form the public prefix of z.
+#[derive(Clone, Debug, PartialEq)]
+pub struct R1csAssignment<E: StarkField> {
+    num_inputs: usize,
+    values: Vec<E>,
+}
+
+impl<E: StarkField> R1csAssignment<E> {
+    /// An assignment with `num_inputs` public inputs and `num_witnesses` witness values, all
+    /// zero, and the constant wire set to one.
+    pub fn new(num_inputs: usize, num_witnesses: usize) -> Self {
+        let mut values = vec![E::ZERO; 1 + num_inputs + num_witnesses];
+        values[0] = E::ONE;
+        R1csAssignment { num_inputs, values }
+    }
+
+    /// Sets the `i`-th public input, i.e. wire `1 + i`.
+    pub fn set_input(&mut self, i: usize, value: E) -> Result<(), R1CSError> {
+        if i >= self.num_inputs {
+            return Err(R1CSError::AssignmentIndexOutOfRange(
+                "input".to_string(),
+                i,
+                self.num_inputs,
+            ));
+        }
+        self.values[1 + i] = value;
+        Ok(())
+    }
+
+    /// Sets the `i`-th witness value, i.e. wire `1 + num_inputs + i`.
+    pub fn set_witness(&mut self, i: usize, value: E) -> Result<(), R1CSError> {
+        let num_witnesses = self.values.len() - 1 - self.num_inputs;
+        if i >= num_witnesses {
+            return Err(R1CSError::AssignmentIndexOutOfRange(
+                "witness".to_string(),
+                i,
+                num_witnesses,
+            ));
+        }
+        self.values[1 + self.num_inputs + i] = value;
+        Ok(())
+    }
+
+    /// The public prefix of the assignment: the constant one followed by the public inputs.
+    pub fn public_inputs(&self) -> &[E] {
+        &self.values[..1 + self.num_inputs]
+    }
+
+    /// The flat assignment vector the prover expects.
+    pub fn to_vec(&self) -> Vec<E> {
+        self.values.clone()
+    }
+}
+
 // TODO: indexed R1CS consisting of 3 indexed matrices
 
 // TODO: Add error here
@@ -386,6 +483,20 @@ mod localtests{
         }
     }
 
+    #[test]
+    fn test_matrix_accessors_and_pretty_string(){
+        let mat = vec![
+            vec![BaseElement::ZERO, BaseElement::ONE, BaseElement::ZERO],
+            vec![BaseElement::new(3u128), BaseElement::ZERO, BaseElement::new(12u128)],
+        ];
+        let matrix = Matrix::new("A", mat).unwrap();
+        assert_eq!(matrix.row(1), &[BaseElement::new(3u128), BaseElement::ZERO, BaseElement::new(12u128)]);
+        assert_eq!(matrix.get(0, 1), BaseElement::ONE);
+        assert_eq!(matrix.get(1, 2), BaseElement::new(12u128));
+        let expected = "A (2 x 3)\n .  1  .\n 3  . 12\n";
+        assert_eq!(matrix.to_pretty_string(), expected);
+    }
+
     fn make_all_ones_matrix_f128(
         matrix_name: &str,
         rows: usize,
@@ -6,7 +6,7 @@ use winter_math::{
 
 use crate::{
     errors::R1CSError,
-    r1cs::{Matrix, R1CS},
+    r1cs::{Matrix, R1csAssignment, R1CS},
 };
 
 type SmallFieldElement17 = smallprimefield::BaseElement<17, 3, 4>;
@@ -67,6 +67,20 @@ fn test_construct_r1cs() {
     );
 }
 
+#[test]
+fn test_r1cs_assignment_layout() {
+    let mut assignment = R1csAssignment::<BaseElement>::new(2, 3);
+    assignment.set_input(1, BaseElement::new(7)).unwrap();
+    assignment.set_witness(0, BaseElement::new(8)).unwrap();
+    assignment.set_witness(2, BaseElement::new(9)).unwrap();
+    assert!(assignment.set_input(2, BaseElement::ONE).is_err());
+    assert!(assignment.set_witness(3, BaseElement::ONE).is_err());
+
+    let z: Vec<BaseElement> = [1, 0, 7, 8, 0, 9].iter().map(|&v| BaseElement::new(v)).collect();
+    assert_eq!(assignment.to_vec(), z);
+    assert_eq!(assignment.public_inputs(), &z[..3]);
+}
+
 /// ***************  HELPERS *************** \\\
 fn make_all_ones_matrix_f128(
     matrix_name: &str,